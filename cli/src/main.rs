@@ -0,0 +1,244 @@
+use std::{path::PathBuf, thread, time::Duration};
+
+use anyhow::{Context, bail};
+use log::{LevelFilter, Metadata, Record};
+use log_uploader_core::{
+    arcdpslog::{Log, Step},
+    history,
+    settings::Settings,
+    sync::SyncState,
+    uploader::{Target, Uploader, UploaderEvent},
+};
+
+const USAGE: &str = "Usage:
+    log-uploader [options] watch [<logdir>]   upload new logs as arcdps writes them
+    log-uploader [options] upload <files>...  upload the given logs and exit
+    log-uploader [options] sync               import all dps.report uploads of the token into the history
+
+Options:
+    --settings <file>  settings.json to use (default: <config dir>/log-uploader/settings.json)
+    --history <file>   upload history to append to (default: next to the settings)
+    --profile <name>   upload with this profile instead of the active one
+    -v                 more logging, repeat for even more";
+
+const TICK: Duration = Duration::from_millis(200);
+
+enum Command {
+    Watch(Option<PathBuf>),
+    Upload(Vec<PathBuf>),
+    Sync,
+}
+
+struct Args {
+    settings: PathBuf,
+    history: Option<PathBuf>,
+    profile: Option<String>,
+    verbosity: usize,
+    command: Command,
+}
+
+impl Args {
+    fn parse() -> anyhow::Result<Self> {
+        let mut settings = None;
+        let mut history = None;
+        let mut profile = None;
+        let mut verbosity = 0;
+        let mut args = std::env::args().skip(1);
+        let command = loop {
+            let Some(arg) = args.next() else {
+                bail!("Missing command");
+            };
+            match arg.as_str() {
+                "--settings" => settings = Some(args.next().context("--settings needs a file")?),
+                "--history" => history = Some(args.next().context("--history needs a file")?),
+                "--profile" => profile = Some(args.next().context("--profile needs a name")?),
+                "-v" | "-vv" | "-vvv" => verbosity += arg.len() - 1,
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                "watch" => break Command::Watch(args.next().map(PathBuf::from)),
+                "sync" => break Command::Sync,
+                "upload" => {
+                    let files: Vec<_> = args.by_ref().map(PathBuf::from).collect();
+                    if files.is_empty() {
+                        bail!("upload needs at least one file");
+                    }
+                    break Command::Upload(files);
+                }
+                _ => bail!("Unknown argument {arg}"),
+            }
+        };
+        if let Some(arg) = args.next() {
+            bail!("Unexpected argument {arg}");
+        }
+        let settings = match settings {
+            Some(s) => PathBuf::from(s),
+            None => dirs_next::config_dir()
+                .context("No config directory, pass --settings")?
+                .join("log-uploader")
+                .join("settings.json"),
+        };
+        Ok(Self {
+            settings,
+            history: history.map(PathBuf::from),
+            profile,
+            verbosity,
+            command,
+        })
+    }
+}
+
+/// Just enough of a logger to see what the workers are doing
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+fn print_event(event: UploaderEvent) {
+    match event {
+        UploaderEvent::Parsed(log) => println!("Parsed {}: {}", log.basename(), log.boss_name()),
+        UploaderEvent::Uploaded(target, log) => match &log.dpsreport {
+            Step::Done(r) if target == Target::DpsReport => {
+                println!("{}: {}", log.boss_name(), r.permalink)
+            }
+            _ => println!("Uploaded {} to {}", log.basename(), target.name()),
+        },
+        UploaderEvent::Failed(target, log) => {
+            eprintln!("Upload of {} to {} failed", log.basename(), target.name())
+        }
+        UploaderEvent::RateLimited => eprintln!("dps.report rate limit reached, retrying later"),
+        UploaderEvent::WatcherLost(path) => eprintln!("Stopped watching {path}"),
+        UploaderEvent::Offline => {
+            eprintln!("Upload servers unreachable, waiting for the connection")
+        }
+        UploaderEvent::Online => println!("Back online, uploading queued logs"),
+    }
+}
+
+/// A log which failed to parse never gets uploaded, so it doesn't count as pending
+fn is_done(log: &Log) -> bool {
+    log.is_finished() || matches!(log.evtc, Step::Error(_))
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    log::set_logger(&LOGGER).expect("Logger to be set only once");
+    log::set_max_level(match args.verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    });
+
+    Settings::from_path(&args.settings)
+        .with_context(|| format!("Failed to load {}", args.settings.display()))?;
+    // The addon persists these, they would leave this waiting forever
+    {
+        let mut settings = Settings::get_mut();
+        settings.paused = false;
+        settings.approval_mode = false;
+        if let Some(profile) = args.profile {
+            if !settings.profiles.iter().any(|p| p.name == profile) {
+                bail!("No profile called {profile}");
+            }
+            settings.active_profile = profile;
+            settings.auto_profile = false;
+        }
+    }
+    let history_path = args
+        .history
+        .unwrap_or_else(|| args.settings.with_file_name("history.jsonl"));
+    history::from_path(&history_path).unwrap_or_else(|e| {
+        log::error!("Failed to load upload history: {e}");
+    });
+
+    match args.command {
+        Command::Watch(logdir) => {
+            if let Some(logdir) = logdir {
+                Settings::get_mut().logpath = logdir.display().to_string();
+            }
+            let logpath = PathBuf::from(Settings::get().logpath());
+            if !logpath.is_dir() {
+                bail!("{} is not a directory", logpath.display());
+            }
+            println!("Watching {}", logpath.display());
+            let mut uploader = Uploader::new(Some(&logpath), false, args.settings, history_path);
+            loop {
+                uploader.tick(print_event);
+                thread::sleep(TICK);
+            }
+        }
+        Command::Upload(files) => {
+            // Pruned logs would be missing from the failure count below
+            {
+                let mut settings = Settings::get_mut();
+                settings.max_logs = usize::MAX;
+                settings.max_log_age_hours = 0;
+            }
+            let mut uploader = Uploader::new(None, false, args.settings, history_path);
+            for file in files {
+                uploader.enqueue(file);
+            }
+            if uploader.logs.is_empty() {
+                bail!("Nothing to upload");
+            }
+            while !uploader.logs.iter().all(is_done) {
+                uploader.tick(print_event);
+                thread::sleep(TICK);
+            }
+            let failed = uploader
+                .logs
+                .iter()
+                .filter(|l| matches!(l.evtc, Step::Error(_)) || l.upload_failed())
+                .count();
+            uploader.shutdown();
+            if failed > 0 {
+                bail!("{failed} log(s) failed");
+            }
+            Ok(())
+        }
+        Command::Sync => {
+            let mut uploader = Uploader::new(None, false, args.settings, history_path);
+            let (profile, token) = {
+                let settings = Settings::get();
+                let token = settings.active().dpsreport_token.clone();
+                (settings.active_profile.clone(), token)
+            };
+            uploader.sync_history(&profile, &token);
+            while uploader.sync == SyncState::Running {
+                uploader.tick(print_event);
+                thread::sleep(TICK);
+            }
+            let state = uploader.sync.clone();
+            uploader.shutdown();
+            match state {
+                SyncState::Done { uploads, imported } => {
+                    println!("{uploads} uploads on dps.report, {imported} added to the history");
+                    Ok(())
+                }
+                SyncState::Failed(e) => bail!("Sync failed: {e}"),
+                SyncState::Idle | SyncState::Running => bail!("Sync did not start"),
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use anyhow::{Error, Result};
+use chrono::DateTime;
+use chrono::Local;
+use revtc::bossdata::BossId;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use std::time::{Duration, Instant};
+use std::{path::PathBuf, time::SystemTime};
+
+use crate::dpsreport::{DpsReportResponse, UploadOptions};
+use crate::eijson::EiJson;
+use crate::evtc::EncounterSummary;
+use crate::evtc::Outcome;
+use crate::multipart::Progress;
+use crate::uploader::Target;
+use crate::util::e;
+
+// Maybe this needs a retry option for retryable errors
+#[derive(Debug)]
+pub enum Step<T> {
+    Pending,
+    Active,
+    Done(T),
+    Skipped,
+    Error(Error),
+    Retry(Instant),
+}
+
+impl<T> Step<T> {
+    /// Sort key for the status columns
+    pub fn rank(&self) -> u8 {
+        match self {
+            Self::Done(_) => 0,
+            Self::Active => 1,
+            Self::Pending | Self::Retry(_) => 2,
+            Self::Skipped => 3,
+            Self::Error(_) => 4,
+        }
+    }
+
+    /// Whether nothing is going to happen with this step anymore
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Done(_) | Self::Skipped | Self::Error(_))
+    }
+
+    pub fn from_value(value: Result<T>) -> Self {
+        match value {
+            Ok(v) => Self::Done(v),
+            Err(e) => Self::Error(e),
+        }
+    }
+}
+
+/// Targets the user picked for a log waiting for approval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Approval {
+    pub dpsreport: bool,
+    pub wingman: bool,
+}
+
+impl Approval {
+    /// Nothing skipped yet, the targets come from the profile once the log is parsed
+    pub const ALL: Self = Self {
+        dpsreport: true,
+        wingman: true,
+    };
+
+    pub fn get(self, target: Target) -> bool {
+        match target {
+            Target::DpsReport => self.dpsreport,
+            Target::Wingman => self.wingman,
+        }
+    }
+
+    pub fn toggle(&mut self, target: Target) {
+        match target {
+            Target::DpsReport => self.dpsreport = !self.dpsreport,
+            Target::Wingman => self.wingman = !self.wingman,
+        }
+    }
+}
+
+pub struct Log {
+    /// Unique for the lifetime of the addon, used to route worker results back to the log
+    pub id: usize,
+    pub location: PathBuf,
+    /// mtime of the log file, arcdps writes it when the encounter ends
+    pub modified: Option<SystemTime>,
+    pub evtc: Step<EncounterSummary>,
+    /// Kill/CM status as determined from the evtc. Available before any upload finished.
+    pub outcome: Option<Outcome>,
+    pub dpsreport: Step<DpsReportResponse>,
+    pub dpsreport_count: u32,
+    /// What the log was (or is being) uploaded to dps.report with
+    pub dpsreport_options: Option<UploadOptions>,
+    /// Name of the profile the log was uploaded with, empty for the default one
+    pub profile: Option<String>,
+    pub wingman: Step<bool>,
+    /// Details fetched from dps.report once the upload is done. Skipped unless they were asked
+    /// for, either by the settings or by opening the detail panel.
+    pub ei_json: Step<EiJson>,
+    /// Bytes sent of the running uploads
+    pub dpsreport_progress: Option<Progress>,
+    pub wingman_progress: Option<Progress>,
+    /// Set to abort the uploads of this log, replaced when they are retried
+    pub cancel: Arc<AtomicBool>,
+    /// Uploads are held back by the [`crate::schedule::Schedule`]
+    pub waiting: bool,
+    /// Set while the log waits for the user to approve the upload
+    pub approval: Option<Approval>,
+    /// Targets the profile of the log uploads to, approving can't add any others
+    pub eligible: Approval,
+    /// Whether the log was already added to the upload history
+    pub recorded: bool,
+}
+
+pub fn format_time(time: SystemTime) -> String {
+    let dt = DateTime::<Local>::from(time);
+    format!("{}", dt.format("%R"))
+}
+
+impl Log {
+    pub fn new(location: PathBuf) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        use Step as S;
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            modified: location.metadata().and_then(|m| m.modified()).ok(),
+            location,
+            evtc: S::Pending,
+            outcome: None,
+            dpsreport: S::Pending,
+            dpsreport_count: 0,
+            dpsreport_options: None,
+            profile: None,
+            wingman: S::Pending,
+            ei_json: S::Skipped,
+            dpsreport_progress: None,
+            wingman_progress: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            waiting: false,
+            approval: None,
+            eligible: Approval::ALL,
+            recorded: false,
+        }
+    }
+
+    /// Whether the encounter was a kill. `None` if we don't know yet.
+    /// Prefers dps.report's verdict over our own.
+    pub fn success(&self) -> Option<bool> {
+        match &self.dpsreport {
+            Step::Done(dpsreport) => Some(dpsreport.encounter.success),
+            _ => self.outcome.as_ref().map(|o| o.success),
+        }
+    }
+
+    /// Encounter start, preferring the log start event over dps.report
+    pub fn start_time(&self) -> Option<DateTime<Local>> {
+        let ts = match (&self.outcome, &self.dpsreport) {
+            (
+                Some(Outcome {
+                    start: Some(ts), ..
+                }),
+                _,
+            ) => *ts,
+            (_, Step::Done(dpsreport)) if dpsreport.encounter_time > 0 => dpsreport.encounter_time,
+            _ => return None,
+        };
+        DateTime::from_timestamp(ts, 0).map(|t| t.with_timezone(&Local))
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        match (&self.dpsreport, &self.outcome) {
+            (Step::Done(dpsreport), _) if dpsreport.encounter.duration > 0 => {
+                Some(Duration::from_secs(dpsreport.encounter.duration))
+            }
+            (_, Some(outcome)) => Some(outcome.duration),
+            _ => None,
+        }
+    }
+
+    /// Encounter end. Falls back to the file mtime if the log wasn't parsed yet.
+    pub fn end_time(&self) -> Option<SystemTime> {
+        match (self.start_time(), self.duration()) {
+            (Some(start), Some(duration)) => Some(SystemTime::from(start) + duration),
+            _ => self.modified,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.evtc.is_finished() && self.dpsreport.is_finished() && self.wingman.is_finished()
+    }
+
+    pub fn is_cm(&self) -> bool {
+        match &self.dpsreport {
+            Step::Done(dpsreport)
+                if dpsreport.encounter.is_cm.is_some()
+                    || dpsreport.encounter.is_legendary_cm.is_some() =>
+            {
+                dpsreport.encounter.is_cm == Some(true)
+                    || dpsreport.encounter.is_legendary_cm == Some(true)
+            }
+            _ => self.outcome.as_ref().and_then(|o| o.is_cm) == Some(true),
+        }
+    }
+
+    pub fn upload_failed(&self) -> bool {
+        matches!(self.dpsreport, Step::Error(_))
+            || matches!(self.wingman, Step::Error(_) | Step::Done(false))
+    }
+
+    pub fn is_uploading(&self) -> bool {
+        matches!(self.dpsreport, Step::Active) || matches!(self.wingman, Step::Active)
+    }
+
+    /// Combined progress of the running uploads
+    pub fn upload_progress(&self) -> Option<Progress> {
+        let running = [
+            (
+                matches!(self.dpsreport, Step::Active),
+                self.dpsreport_progress,
+            ),
+            (matches!(self.wingman, Step::Active), self.wingman_progress),
+        ];
+        running
+            .into_iter()
+            .filter(|(active, _)| *active)
+            .filter_map(|(_, progress)| progress)
+            .reduce(|a, b| Progress {
+                sent: a.sent + b.sent,
+                total: a.total + b.total,
+            })
+    }
+
+    /// Stops the uploads of this log, including the ones which didn't start yet.
+    /// The uploader marks them as cancelled on its next tick.
+    pub fn cancel_uploads(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    pub fn is_awaiting_approval(&self) -> bool {
+        self.approval.is_some()
+    }
+
+    /// Releases the log to the picked targets, the other ones are skipped
+    pub fn approve(&mut self) {
+        let Some(approval) = self.approval.take() else {
+            return;
+        };
+        if !approval.dpsreport {
+            self.dpsreport = Step::Skipped;
+        }
+        if !approval.wingman {
+            self.wingman = Step::Skipped;
+        }
+    }
+
+    /// Picks or skips `target`, the ones the profile doesn't upload to stay skipped
+    pub fn toggle_approval(&mut self, target: Target) {
+        if !self.eligible.get(target) {
+            return;
+        }
+        if let Some(approval) = &mut self.approval {
+            approval.toggle(target);
+        }
+    }
+
+    /// Skips all uploads, the log counts as finished afterwards
+    pub fn reject(&mut self) {
+        self.approval = Some(Approval {
+            dpsreport: false,
+            wingman: false,
+        });
+        self.approve();
+    }
+
+    /// Fetches the Elite Insights json once the dps.report upload is done, again if it failed
+    pub fn request_ei_json(&mut self) {
+        if matches!(self.ei_json, Step::Skipped | Step::Error(_)) {
+            self.ei_json = Step::Pending;
+        }
+    }
+
+    /// Queues failed uploads again with a fresh retry budget
+    pub fn retry_failed(&mut self) {
+        if self.is_cancelled() {
+            self.cancel = Arc::new(AtomicBool::new(false));
+        }
+        if matches!(self.dpsreport, Step::Error(_)) {
+            self.dpsreport = Step::Pending;
+            self.dpsreport_count = 0;
+        }
+        if matches!(self.wingman, Step::Error(_) | Step::Done(false)) {
+            self.wingman = Step::Pending;
+        }
+    }
+
+    /// Name dps.report gave the boss, only our own names are translated
+    pub fn boss_name(&self) -> String {
+        match (&self.dpsreport, &self.evtc) {
+            (Step::Done(dpsreport), _) => dpsreport.encounter.boss.clone(),
+            (_, Step::Done(evtc)) => e(&BossId::from_header_id(evtc.boss_id).to_string()),
+            _ => self.basename(),
+        }
+    }
+
+    /// Case insensitive search over the boss, character and account names.
+    /// `query` has to be lowercase already.
+    pub fn matches_query(&self, query: &str) -> bool {
+        if self.boss_name().to_lowercase().contains(query) {
+            return true;
+        }
+        let Step::Done(evtc) = &self.evtc else {
+            return false;
+        };
+        evtc.players.iter().any(|a| {
+            a.character_name.to_lowercase().contains(query)
+                || a.account_name.to_lowercase().contains(query)
+        })
+    }
+
+    pub fn basename(&self) -> String {
+        self.location
+            .parent()
+            .and_then(|p| p.file_name())
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+}
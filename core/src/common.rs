@@ -0,0 +1,85 @@
+use anyhow::Result;
+
+use crate::{
+    dpsreport::{DpsReportResponse, Retry},
+    eijson::EiJson,
+    evtc::{EncounterSummary, Outcome},
+    multipart::Progress,
+    uploader::Target,
+};
+
+#[derive(Debug)]
+pub struct WorkerMessage {
+    /// Id of the log this message belongs to
+    pub id: usize,
+    pub payload: WorkerType,
+}
+
+impl WorkerMessage {
+    pub fn evtc(id: usize, evtc: Result<(EncounterSummary, Outcome)>) -> Self {
+        Self {
+            id,
+            payload: WorkerType::Evtc(evtc),
+        }
+    }
+
+    pub fn dpsreport(
+        id: usize,
+        profile: String,
+        dpsreport: Result<Result<DpsReportResponse, Retry>>,
+    ) -> WorkerMessage {
+        WorkerMessage {
+            id,
+            payload: WorkerType::DpsReport(profile, dpsreport),
+        }
+    }
+    pub fn progress(id: usize, target: Target, progress: Progress) -> WorkerMessage {
+        WorkerMessage {
+            id,
+            payload: WorkerType::Progress(target, progress),
+        }
+    }
+    pub fn offline(id: usize, target: Target, reason: String) -> WorkerMessage {
+        WorkerMessage {
+            id,
+            payload: WorkerType::Offline(target, reason),
+        }
+    }
+    /// Not about a single log, so the id doesn't belong to any
+    pub fn sync(uploads: Result<Vec<DpsReportResponse>>) -> WorkerMessage {
+        WorkerMessage {
+            id: usize::MAX,
+            payload: WorkerType::Sync(uploads),
+        }
+    }
+    pub fn ei_json(id: usize, json: Result<EiJson>) -> WorkerMessage {
+        WorkerMessage {
+            id,
+            payload: WorkerType::EiJson(json),
+        }
+    }
+    // should be a url later instead of bool
+    pub fn wingman(id: usize, wingman: Result<bool>) -> WorkerMessage {
+        WorkerMessage {
+            id,
+            payload: WorkerType::Wingman(wingman),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WorkerType {
+    /// Comes with the name of the profile the log was uploaded with, the log might be gone
+    DpsReport(String, Result<Result<DpsReportResponse, Retry>>),
+    Wingman(Result<bool>),
+    Evtc(Result<(EncounterSummary, Outcome)>),
+    /// Sent while an upload is running
+    Progress(Target, Progress),
+    /// The server couldn't be reached at all, the upload didn't count as an attempt.
+    /// Comes with the reason, tokens are already masked.
+    Offline(Target, String),
+    /// All uploads of the dps.report token
+    Sync(Result<Vec<DpsReportResponse>>),
+    /// Elite Insights json of an uploaded log
+    EiJson(Result<EiJson>),
+}
@@ -0,0 +1,87 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::Duration,
+};
+
+use ureq::ErrorKind;
+
+use crate::{http, uploader::Target};
+
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Upload servers which couldn't be reached. Set by the uploader, cleared by [`run`] once the
+/// server answers again. Tracked per target, one server being down doesn't hold back the other.
+#[derive(Debug, Default)]
+pub struct Offline {
+    dpsreport: AtomicBool,
+    wingman: AtomicBool,
+}
+
+impl Offline {
+    fn flag(&self, target: Target) -> &AtomicBool {
+        match target {
+            Target::DpsReport => &self.dpsreport,
+            Target::Wingman => &self.wingman,
+        }
+    }
+
+    pub fn get(&self, target: Target) -> bool {
+        self.flag(target).load(Ordering::Relaxed)
+    }
+
+    /// Returns whether the target was offline before
+    pub fn set(&self, target: Target, offline: bool) -> bool {
+        self.flag(target).swap(offline, Ordering::Relaxed)
+    }
+
+    pub fn any(&self) -> bool {
+        self.get(Target::DpsReport) || self.get(Target::Wingman)
+    }
+}
+
+/// We never reached the server, as opposed to the server failing to handle the upload
+pub fn is_connection_error(e: &ureq::Error) -> bool {
+    matches!(e.kind(), ErrorKind::Dns | ErrorKind::ConnectionFailed)
+}
+
+/// Any answer counts, even an error status means the server is reachable
+pub fn probe(endpoint: &str) -> bool {
+    let client = match http::client() {
+        Ok(client) => client,
+        Err(e) => {
+            log::error!("[Connectivity] Invalid network settings: {e}");
+            return false;
+        }
+    };
+    match client.head(endpoint).call() {
+        Err(e) if is_connection_error(&e) => false,
+        _ => true,
+    }
+}
+
+/// Probes the endpoint of every target which is `offline` and clears it once it answers.
+/// Stops when the sender of `shutdown` is dropped.
+pub fn run(
+    shutdown: Receiver<()>,
+    offline: Arc<Offline>,
+    endpoints: Vec<(Target, String)>,
+) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name("connectivity-thread".to_string())
+        .spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = shutdown.recv_timeout(PROBE_INTERVAL) {
+                for (target, endpoint) in &endpoints {
+                    if offline.get(*target) && probe(endpoint) {
+                        log::info!("[Connectivity] {endpoint} is reachable again");
+                        offline.set(*target, false);
+                    }
+                }
+            }
+        })
+        .expect("Could not create connectivity thread")
+}
@@ -0,0 +1,524 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, Sender},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use ureq::Response;
+
+use crate::{
+    common::WorkerMessage,
+    connectivity, http,
+    multipart::{Multipart, Progress},
+    summary::{self, Category},
+    uploader::Target,
+    util::e,
+};
+
+/// (log id, path, token, profile name, upload options, cancel flag)
+pub type DpsJob = (
+    usize,
+    PathBuf,
+    String,
+    String,
+    UploadOptions,
+    Arc<AtomicBool>,
+);
+
+pub const DEFAULT_URL: &str = "https://dps.report";
+
+/// Which tool dps.report renders the report with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Generator {
+    EliteInsights,
+    /// Deprecated by dps.report, only kept for people who still want it
+    RaidHeroes,
+}
+
+impl Generator {
+    pub const ALL: [Self; 2] = [Self::EliteInsights, Self::RaidHeroes];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EliteInsights => "Elite Insights",
+            Self::RaidHeroes => "Raid Heroes",
+        }
+    }
+
+    fn param(self) -> &'static str {
+        match self {
+            Self::EliteInsights => "ei",
+            Self::RaidHeroes => "rh",
+        }
+    }
+}
+
+/// Extra parameters of an upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadOptions {
+    /// Replaces the account and character names in the report
+    pub anonymous: bool,
+    /// Per player stats for WvW logs
+    pub detailed_wvw: bool,
+    pub generator: Generator,
+}
+
+impl UploadOptions {
+    pub const fn default() -> Self {
+        Self {
+            anonymous: false,
+            detailed_wvw: false,
+            generator: Generator::EliteInsights,
+        }
+    }
+
+    /// Short description for tooltips, e.g. `Elite Insights, anonymous`
+    pub fn describe(&self) -> String {
+        let mut parts = vec![self.generator.name().to_string()];
+        if self.anonymous {
+            parts.push(e("anonymous"));
+        }
+        if self.detailed_wvw {
+            parts.push(e("detailed WvW"));
+        }
+        parts.join(", ")
+    }
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryOptions {
+    pub category: Category,
+    pub options: UploadOptions,
+}
+
+/// Upload options with overrides for some categories
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DpsReportOptions {
+    pub default: UploadOptions,
+    pub overrides: Vec<CategoryOptions>,
+}
+
+impl DpsReportOptions {
+    pub const fn default() -> Self {
+        Self {
+            default: UploadOptions::default(),
+            overrides: Vec::new(),
+        }
+    }
+
+    pub fn for_boss(&self, boss_id: u16) -> UploadOptions {
+        let category = summary::category(boss_id).0;
+        self.overrides
+            .iter()
+            .find(|o| o.category == category)
+            .map_or(self.default, |o| o.options)
+    }
+}
+
+impl Default for DpsReportOptions {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+/// Why dps.report wants us to come back later
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryReason {
+    RateLimited,
+    Timeout,
+    ServerError,
+    Forbidden,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    pub at: Instant,
+    pub reason: RetryReason,
+}
+
+impl Retry {
+    fn in_30s(reason: RetryReason) -> Self {
+        Self {
+            at: Instant::now() + Duration::from_secs(30),
+            reason,
+        }
+    }
+}
+
+fn check_json(body: &str) -> Result<Result<DpsReportResponse, Retry>, anyhow::Error> {
+    match serde_json::from_str::<Result<DpsReportResponse, DpsReportError>>(&body) {
+        Ok(json) => {
+            match json {
+                Ok(report) => Ok(Ok(report)), // somehow we got a valid report from an error response
+                Err(e) => {
+                    if e.error.contains("EI Failure")
+                        || e.error.contains("An identical file was uploaded recently")
+                        || e.error.contains("Encounter is too short")
+                    {
+                        Err(anyhow::anyhow!("Error 403: {}", e.error))
+                    } else if e.rate_limited == Some(true) {
+                        Ok(Err(Retry::in_30s(RetryReason::RateLimited)))
+                    } else {
+                        // Generic forbidden. we retry in 30 seconds
+                        Ok(Err(Retry::in_30s(RetryReason::Forbidden)))
+                    }
+                }
+            }
+        }
+        Err(e) => Err(anyhow::anyhow!("Error parsing json: {e}: {body}")),
+    }
+}
+
+/// `endpoint` is the base url of the dps.report instance, see [`DEFAULT_URL`]
+pub fn run(
+    inc: Receiver<DpsJob>,
+    out: Sender<WorkerMessage>,
+    endpoint: String,
+) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name("dpsreport-thread".to_string())
+        .spawn(move || {
+            for (id, location, token, profile, options, cancel) in inc {
+                // Cancelled while queued, the log already shows it
+                if cancel.load(Ordering::Relaxed) {
+                    continue;
+                }
+                log::info!("dpsreport for {:?}", location);
+                let progress = {
+                    let out = out.clone();
+                    move |p| {
+                        // The main thread only stops listening when unloading
+                        let _ = out.send(WorkerMessage::progress(id, Target::DpsReport, p));
+                    }
+                };
+                let message = match http::client() {
+                    Ok(client) => match upload_file(
+                        &client,
+                        &endpoint,
+                        &location,
+                        &token,
+                        options,
+                        cancel.clone(),
+                        progress,
+                    ) {
+                        Err(e) if connectivity::is_connection_error(&e) => {
+                            // The error contains the request url with the token
+                            let reason = redact(format!("Could not reach {endpoint}: {e}"), &token);
+                            log::warn!("[DpsReport] {reason}");
+                            WorkerMessage::offline(id, Target::DpsReport, reason)
+                        }
+                        res => WorkerMessage::dpsreport(id, profile, handle_response(res, &token)),
+                    },
+                    Err(e) => WorkerMessage::dpsreport(
+                        id,
+                        profile,
+                        Err(e.context("Invalid network settings")),
+                    ),
+                };
+                if cancel.load(Ordering::Relaxed) {
+                    log::info!("[DpsReport] Upload of {} cancelled", location.display());
+                    continue;
+                }
+                if let Err(e) = out.send(message) {
+                    log::error!("[DpsReport] Failed to send dpsreport result to main thread: {e}");
+                }
+            }
+        })
+        .expect("Could not create dpsreport thread")
+}
+
+/// Masks the token in messages which end up in the log or the ui
+fn redact(msg: String, token: &str) -> String {
+    // token gets set afterwards in main thread again
+    // this should only happen on first install if no custom token is set
+    if token.is_empty() {
+        msg
+    } else {
+        msg.replace(token, "******")
+    }
+}
+
+fn handle_response(
+    res: Result<Response, ureq::Error>,
+    token: &str,
+) -> Result<Result<DpsReportResponse, Retry>, anyhow::Error> {
+    match res {
+        Err(ureq::Error::Status(status, res)) => match status {
+            408 => Ok(Err(Retry::in_30s(RetryReason::Timeout))),
+            429 => Ok(Err(Retry::in_30s(RetryReason::RateLimited))),
+            status if status >= 500 => Ok(Err(Retry::in_30s(RetryReason::ServerError))),
+            403 => {
+                let body = res.into_string().unwrap_or_default();
+                check_json(&body)
+            }
+            _ => Err(anyhow::anyhow!("Unknown error {}", res.status())),
+        },
+        Err(e) => {
+            let msg = redact(format!("Failed to upload file: {e}"), token);
+            log::error!("[DpsReport] {msg}");
+            Err(anyhow::anyhow!(msg))
+        }
+        Ok(res) => {
+            log::info!(
+                "[DpsReport] Response: {}",
+                redact(format!("{res:?}"), token)
+            );
+
+            if (200..300).contains(&res.status()) {
+                let body = res.into_string().unwrap_or_default();
+                match serde_json::from_str::<DpsReportResponse>(&body) {
+                    Ok(json) => Ok(Ok(json)),
+                    Err(e) => Err(anyhow::anyhow!("Error parsing json: {e}: {body}")),
+                }
+            } else {
+                Err(anyhow::anyhow!("Unknown Response Code: {}", res.status()))
+            }
+        }
+    }
+}
+
+fn upload_file(
+    client: &ureq::Agent,
+    endpoint: &str,
+    location: &Path,
+    token: &str,
+    options: UploadOptions,
+    cancel: Arc<AtomicBool>,
+    progress: impl FnMut(Progress) + Send + 'static,
+) -> Result<Response, ureq::Error> {
+    log::info!("[DpsReport] Uploading {}", location.display());
+
+    let body = Multipart::new().file("file", location)?;
+    let mut req = client
+        .post(&format!("{endpoint}/uploadContent"))
+        .query("json", "1");
+    if !token.is_empty() {
+        req = req.query("userToken", token);
+    }
+    req = req.query("generator", options.generator.param());
+    if options.anonymous {
+        req = req.query("anonymous", "true");
+    }
+    if options.detailed_wvw {
+        req = req.query("detailedwvw", "true");
+    }
+    req.set("Content-Type", &body.content_type)
+        .set("Content-Length", &body.len.to_string())
+        .send(body.with_progress(cancel, progress))
+}
+
+/// dps.report sends the players keyed by account, older reports as a list
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum PlayerResponse {
+    Seq(Vec<Player>),
+    Map(HashMap<String, Player>),
+}
+
+impl PlayerResponse {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Seq(players) => players.is_empty(),
+            Self::Map(players) => players.is_empty(),
+        }
+    }
+
+    /// Sorted by account, the map has no order
+    pub fn to_vec(&self) -> Vec<&Player> {
+        let mut players: Vec<_> = match self {
+            Self::Seq(players) => players.iter().collect(),
+            Self::Map(players) => players.values().collect(),
+        };
+        players.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+        players
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DpsReportResponse {
+    pub id: String,
+    pub permalink: String,
+    /// Not part of the uploads listed by `getUploads`
+    #[serde(default)]
+    pub user_token: String,
+    /// Unix timestamp of the upload
+    #[serde(default)]
+    pub upload_time: i64,
+    /// Unix timestamp of the encounter start
+    #[serde(default)]
+    pub encounter_time: i64,
+    /// Name of the parser, e.g. "Elite Insights"
+    #[serde(default)]
+    pub generator: String,
+    #[serde(default)]
+    pub generator_version: u32,
+    #[serde(default)]
+    pub evtc: EvtcInfo,
+    pub encounter: Encounter,
+    pub players: PlayerResponse,
+}
+
+impl DpsReportResponse {
+    pub fn uploaded_at(&self) -> Option<SystemTime> {
+        let secs = u64::try_from(self.upload_time).ok().filter(|s| *s > 0)?;
+        Some(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+}
+
+/// What dps.report found in the header of the log
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EvtcInfo {
+    /// Format of the file, "EVTC" for arcdps logs
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// arcdps build which wrote the log, e.g. "EVTC20240612"
+    pub version: String,
+    pub boss_id: i64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Encounter {
+    pub boss_id: i64,
+    pub success: bool,
+    pub boss: String,
+    /// Fight duration in seconds
+    #[serde(default)]
+    pub duration: u64,
+    /// Damage per second of the whole squad
+    #[serde(default)]
+    pub comp_dps: u64,
+    #[serde(default)]
+    pub number_of_players: u32,
+    #[serde(default)]
+    pub number_of_groups: u32,
+    #[serde(default)]
+    pub gw2_build: u64,
+    /// Whether the Elite Insights json can be fetched with `getJson`
+    #[serde(default)]
+    pub json_available: bool,
+    pub is_cm: Option<bool>,
+    pub is_legendary_cm: Option<bool>,
+    pub emboldened: Option<i32>,
+}
+
+impl Encounter {
+    pub fn format_mode(&self) -> Option<String> {
+        // WvW logs don't have any other modes
+        if self.boss_id == 1 {
+            return Some("".into());
+        }
+        match (self.emboldened, self.is_cm, self.is_legendary_cm) {
+            (_, _, Some(true)) => Some("LCM".into()),
+            (_, Some(true), _) => Some("CM".into()),
+            (Some(n @ 1..), _, _) => Some(format!("Emboldened {n})")),
+            (Some(0), Some(false), Some(false)) => Some("".into()),
+            _ => None,
+        }
+    }
+}
+
+// dps.report uses snake case for the players only, accept camel case in case that changes
+#[derive(Debug, Deserialize, Clone)]
+pub struct Player {
+    #[serde(alias = "displayName")]
+    pub display_name: String,
+    #[serde(alias = "characterName")]
+    pub character_name: String,
+    pub profession: u32,
+    #[serde(alias = "eliteSpec")]
+    pub elite_spec: u32,
+}
+
+/// Professions by their id in the game api
+const PROFESSIONS: [&str; 9] = [
+    "Guardian",
+    "Warrior",
+    "Engineer",
+    "Ranger",
+    "Thief",
+    "Elementalist",
+    "Mesmer",
+    "Necromancer",
+    "Revenant",
+];
+
+/// Elite specializations by their id in the game api
+const ELITE_SPECS: [(u32, &str); 36] = [
+    (5, "Druid"),
+    (7, "Daredevil"),
+    (18, "Berserker"),
+    (27, "Dragonhunter"),
+    (34, "Reaper"),
+    (40, "Chronomancer"),
+    (43, "Scrapper"),
+    (48, "Tempest"),
+    (52, "Herald"),
+    (55, "Soulbeast"),
+    (56, "Weaver"),
+    (57, "Holosmith"),
+    (58, "Deadeye"),
+    (59, "Mirage"),
+    (60, "Scourge"),
+    (61, "Spellbreaker"),
+    (62, "Firebrand"),
+    (63, "Renegade"),
+    (64, "Harbinger"),
+    (65, "Willbender"),
+    (66, "Virtuoso"),
+    (67, "Catalyst"),
+    (68, "Bladesworn"),
+    (69, "Vindicator"),
+    (70, "Mechanist"),
+    (71, "Specter"),
+    (72, "Untamed"),
+    (73, "Troubadour"),
+    (74, "Paragon"),
+    (75, "Amalgam"),
+    (76, "Ritualist"),
+    (77, "Antiquary"),
+    (78, "Galeshot"),
+    (79, "Conduit"),
+    (80, "Evoker"),
+    (81, "Luminary"),
+];
+
+impl Player {
+    /// Name of the elite specialization, the profession for core builds and unknown ones
+    pub fn specialization(&self) -> &'static str {
+        ELITE_SPECS
+            .iter()
+            .find(|(id, _)| *id == self.elite_spec)
+            .map(|(_, name)| *name)
+            .or_else(|| {
+                let index = (self.profession as usize).checked_sub(1)?;
+                PROFESSIONS.get(index).copied()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DpsReportError {
+    pub error: String,
+    pub rate_limited: Option<bool>,
+    pub rate_per_minute: Option<u32>,
+}
@@ -0,0 +1,254 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, create_dir_all},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, Sender},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{common::WorkerMessage, http};
+
+/// (log id, dps.report id)
+pub type EiJob = (usize, String);
+
+/// Cached json older than this is deleted when the worker starts
+pub const CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Boons listed in the detail panel: (buff id, name, whether generation is counted in stacks)
+pub const BOONS: [(i64, &str, bool); 6] = [
+    (740, "Might", true),
+    (725, "Fury", false),
+    (1187, "Quickness", false),
+    (30328, "Alacrity", false),
+    (717, "Protection", false),
+    (1122, "Stability", true),
+];
+
+/// Status mechanics Elite Insights adds to every log, they only track what happened to a player
+const STATUS_MECHANICS: [&str; 3] = ["Got up", "Resp", "DC"];
+
+/// The parts of the Elite Insights json the detail panel needs. Stored in the cache with the
+/// names Elite Insights uses, so both parse the same way.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EiJson {
+    pub fight_name: String,
+    #[serde(rename = "durationMS")]
+    pub duration_ms: u64,
+    pub success: bool,
+    pub players: Vec<EiPlayer>,
+    pub mechanics: Vec<EiMechanic>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EiPlayer {
+    /// Character name
+    pub name: String,
+    pub account: String,
+    pub profession: String,
+    pub group: u32,
+    /// Friendly NPCs and players who left the squad
+    pub not_in_squad: bool,
+    /// Per target, per phase. The first phase is the whole fight.
+    pub dps_targets: Vec<Vec<EiDps>>,
+    /// Per phase, against everything
+    pub dps_all: Vec<EiDps>,
+    /// Boons given to the squad
+    pub squad_buffs: Vec<EiBuff>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EiDps {
+    pub dps: u64,
+    pub damage: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EiBuff {
+    pub id: i64,
+    /// Per phase
+    pub buff_data: Vec<EiBuffData>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EiBuffData {
+    /// Percent of the fight for duration boons, average stacks for intensity boons
+    pub generation: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EiMechanic {
+    pub name: String,
+    pub description: String,
+    /// Tracks who is still eligible for an achievement, not something to avoid
+    pub is_achievement_eligibility: bool,
+    pub mechanics_data: Vec<EiMechanicData>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EiMechanicData {
+    /// Milliseconds since the fight start
+    pub time: i64,
+    /// Character name
+    pub actor: String,
+}
+
+impl EiPlayer {
+    /// Damage per second against the fight targets over the whole fight
+    pub fn target_dps(&self) -> u64 {
+        if self.dps_targets.is_empty() {
+            return self.dps_all.first().map_or(0, |d| d.dps);
+        }
+        self.dps_targets
+            .iter()
+            .filter_map(|phases| phases.first())
+            .map(|d| d.dps)
+            .sum()
+    }
+
+    /// Generation of the boon over the whole fight, 0 if the player didn't give any
+    pub fn boon_generation(&self, id: i64) -> f64 {
+        self.squad_buffs
+            .iter()
+            .find(|b| b.id == id)
+            .and_then(|b| b.buff_data.first())
+            .map_or(0.0, |d| d.generation)
+    }
+}
+
+impl EiMechanic {
+    /// Elite Insights doesn't flag failures, so everything but its status and achievement
+    /// mechanics counts as one
+    pub fn is_failure(&self) -> bool {
+        !self.is_achievement_eligibility && !STATUS_MECHANICS.contains(&self.name.as_str())
+    }
+}
+
+impl EiJson {
+    /// Squad members, highest dps against the targets first
+    pub fn dps_ranking(&self) -> Vec<&EiPlayer> {
+        let mut players: Vec<_> = self.players.iter().filter(|p| !p.not_in_squad).collect();
+        players.sort_by_key(|p| std::cmp::Reverse(p.target_dps()));
+        players
+    }
+
+    /// How often each squad member failed each mechanic, ordered by character name
+    pub fn failures_per_player(&self) -> BTreeMap<&str, BTreeMap<&str, usize>> {
+        let mut counts: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+        for m in self.mechanics.iter().filter(|m| m.is_failure()) {
+            for d in &m.mechanics_data {
+                let in_squad = self
+                    .players
+                    .iter()
+                    .any(|p| !p.not_in_squad && p.name == d.actor);
+                if in_squad {
+                    *counts
+                        .entry(d.actor.as_str())
+                        .or_default()
+                        .entry(m.name.as_str())
+                        .or_default() += 1;
+                }
+            }
+        }
+        counts
+    }
+}
+
+fn cache_file(cache_dir: &Path, id: &str) -> PathBuf {
+    let name: String = id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        .collect();
+    cache_dir.join(format!("{name}.json"))
+}
+
+fn read_cache(path: &Path) -> Option<EiJson> {
+    let file = File::open(path).ok()?;
+    serde_json::from_reader(BufReader::new(file))
+        .inspect_err(|e| log::warn!("[EI] Ignoring broken cache {}: {e}", path.display()))
+        .ok()
+}
+
+fn write_cache(path: &Path, json: &EiJson) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir)?;
+    }
+    serde_json::to_writer(BufWriter::new(File::create(path)?), json)?;
+    Ok(())
+}
+
+/// Deletes cached json older than `max_age`. The logs it belongs to left the list long ago.
+pub fn prune_cache(cache_dir: &Path, max_age: Duration) {
+    // Nothing was cached yet
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| now.duration_since(t).ok())
+            .is_some_and(|age| age > max_age);
+        if !expired || path.extension().is_none_or(|e| e != "json") {
+            continue;
+        }
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!("[EI] Failed to delete {}: {e}", path.display());
+        }
+    }
+}
+
+/// The Elite Insights json of the dps.report upload `id`, from the cache if it was fetched before
+pub fn fetch(endpoint: &str, cache_dir: &Path, id: &str) -> anyhow::Result<EiJson> {
+    let path = cache_file(cache_dir, id);
+    if let Some(json) = read_cache(&path) {
+        log::debug!("[EI] Using cached json for {id}");
+        return Ok(json);
+    }
+    log::info!("[EI] Fetching json for {id}");
+    let json: EiJson = http::client()
+        .context("Invalid network settings")?
+        .get(&format!("{endpoint}/getJson"))
+        .query("id", id)
+        .call()?
+        .into_json()
+        .context("Invalid Elite Insights json")?;
+    if let Err(e) = write_cache(&path, &json) {
+        log::error!("[EI] Failed to cache json for {id}: {e}");
+    }
+    Ok(json)
+}
+
+pub fn run(
+    inc: Receiver<EiJob>,
+    out: Sender<WorkerMessage>,
+    endpoint: String,
+    cache_dir: PathBuf,
+) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name("eijson-thread".to_string())
+        .spawn(move || {
+            prune_cache(&cache_dir, CACHE_MAX_AGE);
+            for (id, report) in inc {
+                let res = fetch(&endpoint, &cache_dir, &report);
+                if let Err(e) = out.send(WorkerMessage::ei_json(id, res)) {
+                    log::error!("[EI] Failed to send json to main thread: {e}");
+                }
+            }
+        })
+        .expect("Could not create eijson thread")
+}
@@ -0,0 +1,230 @@
+use std::fmt::Write;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use revtc::{
+    bossdata::{EliteSpec, Profession},
+    evtc::{Agent, Encounter, StateChange},
+};
+
+use crate::{common::WorkerMessage, util::e_fill};
+
+/// (log id, path)
+pub type EvtcJob = (usize, PathBuf);
+
+pub fn run(inc: Receiver<EvtcJob>, out: Sender<WorkerMessage>) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name("uploader-evtc".to_string())
+        .spawn(move || {
+            loop {
+                match inc.recv() {
+                    Ok((id, path)) => {
+                        set_background_priority(true);
+                        log::trace!("[EVTC] Processing {}", path.display());
+                        // Only the summary is kept, the full encounter is dropped right here
+                        let evtc = revtc::open(path).map(|e| {
+                            (
+                                EncounterSummary::from_encounter(&e),
+                                Outcome::from_encounter(&e),
+                            )
+                        });
+
+                        if let Err(e) = out.send(WorkerMessage::evtc(id, evtc)) {
+                            log::error!("[EVTC] Failed to send evtc to main thread: {e}");
+                        };
+                        set_background_priority(false);
+                    }
+                    Err(e) => {
+                        log::trace!("[EVTC] Worker thread exiting: {e}");
+                        break;
+                    }
+                }
+            }
+        })
+        .unwrap()
+}
+
+// Parsing competes with the game for cpu time, so it runs with a lower priority
+#[cfg(windows)]
+fn set_background_priority(background: bool) {
+    use windows::Win32::System::Threading::{
+        GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_NORMAL,
+    };
+    let priority = if background {
+        THREAD_PRIORITY_BELOW_NORMAL
+    } else {
+        THREAD_PRIORITY_NORMAL
+    };
+    unsafe {
+        if let Err(e) = SetThreadPriority(GetCurrentThread(), priority) {
+            log::error!(
+                "[EVTC] Failed to set thread priority ({}): {}",
+                e.code(),
+                e.message()
+            );
+        }
+    };
+}
+
+#[cfg(not(windows))]
+fn set_background_priority(_background: bool) {}
+
+/// A player that took part in the encounter
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub character_name: String,
+    pub account_name: String,
+    /// Texture identifier of the profession icon
+    pub icon: String,
+}
+
+/// The parts of a parsed evtc the uploader needs. Keeping the full encounter (agents, skills and
+/// events) around for every log adds up over a long session.
+#[derive(Debug, Clone)]
+pub struct EncounterSummary {
+    pub boss_id: u16,
+    /// Account name of the player who recorded the log
+    pub pov: Option<String>,
+    pub players: Vec<Player>,
+}
+
+impl EncounterSummary {
+    pub fn from_encounter(enc: &Encounter) -> Self {
+        Self {
+            boss_id: enc.header.boss_id,
+            pov: enc.pov.as_ref().map(|a| a.account_name.clone()),
+            players: enc
+                .agents
+                .iter()
+                .filter(|a| !a.account_name.is_empty())
+                .map(|a| Player {
+                    character_name: a.character_name.clone(),
+                    account_name: a.account_name.clone(),
+                    icon: identifier_from_agent(a),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn identifier_from_agent(agent: &Agent) -> String {
+    let mut ret = String::new();
+    if agent.elite_spec != EliteSpec::Unknown {
+        write!(ret, "{}", agent.elite_spec).ok();
+    } else if agent.prof != Profession::Unknown {
+        write!(ret, "{}", agent.prof).ok();
+    }
+    icon_identifier(&ret)
+}
+
+/// Texture identifier of the icon of a profession or elite specialization, by its english name
+pub fn icon_identifier(name: &str) -> String {
+    format!("UPLOADER_{}_16x16", name.to_uppercase())
+}
+
+// Reward types arcdps logs for raid and strike boss kills
+const KILL_REWARD_TYPES: [i32; 2] = [55821, 60685];
+const EMBOLDENED: u32 = 68087;
+
+// Max health above which the encounter is in challenge mode. Bosses which don't differ in health
+// (e.g. Cairn, Keep Construct) can't be detected this way and are only known after dps.report
+// responds.
+const CM_HEALTH_THRESHOLDS: &[(u16, u64)] = &[
+    (17172, 25_000_000), // Mursaat Overseer
+    (17188, 30_000_000), // Samarog
+    (17154, 40_000_000), // Deimos
+    (19767, 40_000_000), // Soulless Horror
+    (19450, 35_000_000), // Dhuum
+    (21105, 18_000_000), // Twin Largos (Nikare)
+    (21089, 18_000_000), // Twin Largos (Kenut)
+    (20934, 21_000_000), // Qadim
+    (22006, 23_000_000), // Cardinal Adina
+    (21964, 32_000_000), // Cardinal Sabir
+    (22000, 48_000_000), // Qadim the Peerless
+    (17632, 5_500_000),  // Skorvald
+];
+
+/// Result of an encounter as far as we can tell from the evtc alone
+#[derive(Debug, Clone, Default)]
+pub struct Outcome {
+    pub success: bool,
+    /// Unix timestamp of the log start according to the server
+    pub start: Option<i64>,
+    pub duration: Duration,
+    /// `None` for bosses whose challenge mode can't be told from the evtc
+    pub is_cm: Option<bool>,
+    pub emboldened: u8,
+}
+
+impl Outcome {
+    pub fn from_encounter(enc: &Encounter) -> Self {
+        let boss_id = enc.header.boss_id;
+        let bosses: Vec<u64> = enc
+            .agents
+            .iter()
+            .filter(|a| a.species_id == boss_id)
+            .map(|a| a.address)
+            .collect();
+        let pov = enc.pov.as_ref().map(|a| a.address);
+
+        let mut outcome = Self::default();
+        let (mut first, mut last) = (u64::MAX, 0);
+        let mut max_health = 0;
+        // Bosses can go down between phases, a death only counts if the boss doesn't come back
+        let mut boss_dead = false;
+        for ev in &enc.events {
+            first = first.min(ev.time);
+            last = last.max(ev.time);
+            match ev.is_statechange {
+                // value is the server unix timestamp
+                StateChange::LogStart => {
+                    outcome.start = Some(ev.value as u32 as i64);
+                }
+                StateChange::Reward if KILL_REWARD_TYPES.contains(&ev.value) => {
+                    outcome.success = true;
+                }
+                StateChange::ChangeDead if bosses.contains(&ev.src_agent) => {
+                    boss_dead = true;
+                }
+                // dst_agent is the health percentage * 100
+                StateChange::HealthUpdate if bosses.contains(&ev.src_agent) && ev.dst_agent > 0 => {
+                    boss_dead = false;
+                }
+                StateChange::MaxHealthUpdate if bosses.contains(&ev.src_agent) => {
+                    max_health = max_health.max(ev.dst_agent);
+                }
+                StateChange::BuffInitial
+                    if ev.skillid == EMBOLDENED && Some(ev.dst_agent) == pov =>
+                {
+                    outcome.emboldened = outcome.emboldened.saturating_add(1);
+                }
+                _ => {}
+            }
+        }
+        outcome.success |= boss_dead;
+        if first < last {
+            outcome.duration = Duration::from_millis(last - first);
+        }
+        outcome.is_cm = CM_HEALTH_THRESHOLDS
+            .iter()
+            .find(|(id, _)| *id == boss_id)
+            .filter(|_| max_health > 0)
+            .map(|(_, threshold)| max_health > *threshold);
+        outcome
+    }
+
+    /// `None` if the mode couldn't be determined
+    pub fn format_mode(&self) -> Option<String> {
+        match self.is_cm {
+            Some(true) => Some("CM".into()),
+            // Emboldened is for normal mode only
+            _ if self.emboldened > 0 => Some(e_fill("Emboldened {}", &[&self.emboldened])),
+            Some(false) => Some("".into()),
+            None => None,
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::HashSet,
+    fs::{File, create_dir_all},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+use revtc::bossdata::BossId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arcdpslog::{Log, Step},
+    dpsreport::DpsReportResponse,
+};
+
+/// Outcome of a single uploaded log, persisted across game sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub boss_id: u16,
+    pub boss: String,
+    pub success: bool,
+    #[serde(default)]
+    pub is_cm: bool,
+    #[serde(default)]
+    pub is_legendary_cm: bool,
+    /// Fight duration in seconds
+    pub duration: u64,
+    /// Unix timestamp of the encounter start
+    pub time: i64,
+    #[serde(default)]
+    pub permalink: Option<String>,
+}
+
+impl Entry {
+    /// Uses the dps.report response if there is one, otherwise the outcome parsed from the evtc
+    pub fn from_log(log: &Log) -> Option<Self> {
+        let Step::Done(enc) = &log.evtc else {
+            return None;
+        };
+        let dpsreport = match &log.dpsreport {
+            Step::Done(dpsreport) => Some(dpsreport),
+            _ => None,
+        };
+        let outcome = log.outcome.as_ref();
+        let success = log.success()?;
+        let duration = dpsreport
+            .map(|r| r.encounter.duration)
+            .or(outcome.map(|o| o.duration.as_secs()))
+            .unwrap_or_default();
+        let time = match log.start_time() {
+            Some(start) => start.timestamp(),
+            None => log
+                .modified
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64 - duration as i64)
+                .unwrap_or_default(),
+        };
+        Some(Self {
+            path: log.location.clone(),
+            boss_id: enc.boss_id,
+            boss: BossId::from_header_id(enc.boss_id).to_string(),
+            success,
+            is_cm: dpsreport
+                .and_then(|r| r.encounter.is_cm)
+                .or(outcome.and_then(|o| o.is_cm))
+                .unwrap_or_default(),
+            is_legendary_cm: dpsreport
+                .and_then(|r| r.encounter.is_legendary_cm)
+                .unwrap_or_default(),
+            duration,
+            time,
+            permalink: dpsreport.map(|r| r.permalink.clone()),
+        })
+    }
+
+    /// An upload dps.report told us about. `path` is empty if there's no local file for it.
+    pub fn from_upload(upload: &DpsReportResponse, path: Option<PathBuf>) -> Self {
+        let encounter = &upload.encounter;
+        Self {
+            path: path.unwrap_or_default(),
+            boss_id: encounter.boss_id as u16,
+            // Same name as the local logs, dps.report names some bosses differently
+            boss: BossId::from_header_id(encounter.boss_id as u16).to_string(),
+            success: encounter.success,
+            is_cm: encounter.is_cm.unwrap_or_default(),
+            is_legendary_cm: encounter.is_legendary_cm.unwrap_or_default(),
+            duration: encounter.duration,
+            time: upload.encounter_time,
+            permalink: Some(upload.permalink.clone()),
+        }
+    }
+
+    fn local_path(&self) -> Option<&Path> {
+        (!self.path.as_os_str().is_empty()).then_some(&self.path)
+    }
+}
+
+/// Entries already in the history. Uploads are the same no matter where they came from, local
+/// logs are identified by path.
+#[derive(Default)]
+struct Known {
+    permalinks: HashSet<String>,
+    /// Paths of the entries without a permalink
+    unlinked: HashSet<PathBuf>,
+    paths: HashSet<PathBuf>,
+}
+
+impl Known {
+    fn contains(&self, entry: &Entry) -> bool {
+        let path = entry.local_path();
+        match &entry.permalink {
+            Some(permalink) => {
+                self.permalinks.contains(permalink)
+                    || path.is_some_and(|p| self.unlinked.contains(p))
+            }
+            None => path.is_some_and(|p| self.paths.contains(p)),
+        }
+    }
+
+    fn insert(&mut self, entry: &Entry) {
+        if let Some(permalink) = &entry.permalink {
+            self.permalinks.insert(permalink.clone());
+        }
+        if let Some(path) = entry.local_path() {
+            if entry.permalink.is_none() {
+                self.unlinked.insert(path.to_path_buf());
+            }
+            self.paths.insert(path.to_path_buf());
+        }
+    }
+}
+
+// Stored as json lines so recording a log only has to append to the file
+static HISTORY: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+pub fn get() -> MutexGuard<'static, Vec<Entry>> {
+    HISTORY.lock().unwrap()
+}
+
+pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut entries = vec![];
+    for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // A single broken line (e.g. game crashed while writing) shouldn't lose the history
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => log::warn!("Skipping invalid history entry in line {}: {e}", i + 1),
+        }
+    }
+    *HISTORY.lock().unwrap() = entries;
+    Ok(())
+}
+
+/// Adds the entry to the history and appends it to the history file.
+/// Logs that are already part of the history are ignored, returns whether it was added.
+pub fn record(entry: Entry, path: impl AsRef<Path>) -> anyhow::Result<bool> {
+    Ok(record_all([entry], path)? == 1)
+}
+
+/// Like [`record`] for many entries at once, the file is only opened once.
+/// Returns how many were added.
+pub fn record_all(
+    entries: impl IntoIterator<Item = Entry>,
+    path: impl AsRef<Path>,
+) -> anyhow::Result<usize> {
+    let mut history = HISTORY.lock().unwrap();
+    let mut known = Known::default();
+    history.iter().for_each(|e| known.insert(e));
+    let new: Vec<_> = entries
+        .into_iter()
+        .filter(|e| {
+            let is_new = !known.contains(e);
+            if is_new {
+                known.insert(e);
+            }
+            is_new
+        })
+        .collect();
+    if new.is_empty() {
+        return Ok(0);
+    }
+    let path = path.as_ref();
+    create_dir_all(path.parent().unwrap())?;
+    let mut file = BufWriter::new(File::options().create(true).append(true).open(path)?);
+    for entry in &new {
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    }
+    file.flush()?;
+    let added = new.len();
+    history.extend(new);
+    Ok(added)
+}
+
+pub fn clear() {
+    HISTORY.lock().unwrap().clear();
+}
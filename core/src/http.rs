@@ -0,0 +1,214 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Context;
+use rustls_pki_types::{CertificateDer, pem::PemObject};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+pub const USER_AGENT: &str = concat!("log-uploader/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProxyMode {
+    None,
+    /// `HTTPS_PROXY`/`ALL_PROXY`/`HTTP_PROXY`, on windows also the internet settings
+    System,
+    Manual,
+}
+
+impl ProxyMode {
+    pub const ALL: [Self; 3] = [Self::None, Self::System, Self::Manual];
+}
+
+/// Settings of the http client shared by all upload targets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Http {
+    /// Timeouts in seconds, 0 waits forever
+    pub connect_timeout: u64,
+    pub read_timeout: u64,
+    pub write_timeout: u64,
+    pub proxy: ProxyMode,
+    /// Used with [`ProxyMode::Manual`], e.g. `http://proxy:8080` or `socks5://localhost:1080`
+    pub proxy_url: String,
+    /// PEM file with root certificates to trust in addition to the builtin ones
+    pub ca_file: String,
+}
+
+impl Http {
+    pub const fn default() -> Self {
+        Self {
+            connect_timeout: 10,
+            // dps.report only answers once elite insights is done with the log
+            read_timeout: 120,
+            write_timeout: 120,
+            proxy: ProxyMode::System,
+            proxy_url: String::new(),
+            ca_file: String::new(),
+        }
+    }
+}
+
+impl Default for Http {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+// The agent is cheap to clone and shares its connection pool
+static CLIENT: Mutex<Option<(Http, ureq::Agent)>> = Mutex::new(None);
+
+/// The agent every upload goes through. Rebuilt whenever the settings changed.
+pub fn client() -> anyhow::Result<ureq::Agent> {
+    let http = Settings::get().http.clone();
+    let mut client = CLIENT.lock().unwrap();
+    match client.as_ref() {
+        Some((config, agent)) if *config == http => Ok(agent.clone()),
+        _ => {
+            let agent = build(&http)?;
+            *client = Some((http, agent.clone()));
+            Ok(agent)
+        }
+    }
+}
+
+pub fn build(http: &Http) -> anyhow::Result<ureq::Agent> {
+    let timeout = |secs| (secs > 0).then(|| Duration::from_secs(secs));
+    let mut builder = ureq::AgentBuilder::new().user_agent(USER_AGENT);
+    if let Some(t) = timeout(http.connect_timeout) {
+        builder = builder.timeout_connect(t);
+    }
+    if let Some(t) = timeout(http.read_timeout) {
+        builder = builder.timeout_read(t);
+    }
+    if let Some(t) = timeout(http.write_timeout) {
+        builder = builder.timeout_write(t);
+    }
+    let proxy = match http.proxy {
+        ProxyMode::None => None,
+        ProxyMode::System => system_proxy(),
+        ProxyMode::Manual => Some(http.proxy_url.clone()),
+    };
+    if let Some(proxy) = proxy {
+        let proxy = ureq::Proxy::new(&proxy).with_context(|| format!("Invalid proxy {proxy}"))?;
+        builder = builder.proxy(proxy);
+    }
+    if !http.ca_file.is_empty() {
+        builder = builder.tls_config(Arc::new(tls_config(Path::new(&http.ca_file))?));
+    }
+    Ok(builder.build())
+}
+
+fn tls_config(ca_file: &Path) -> anyhow::Result<rustls::ClientConfig> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let certs = CertificateDer::pem_file_iter(ca_file)
+        .with_context(|| format!("Failed to read {}", ca_file.display()))?;
+    for cert in certs {
+        let cert = cert.with_context(|| format!("Invalid certificate in {}", ca_file.display()))?;
+        roots.add(cert)?;
+    }
+    Ok(rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+fn system_proxy() -> Option<String> {
+    [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+    ]
+    .iter()
+    .filter_map(|var| std::env::var(var).ok())
+    .find(|proxy| !proxy.is_empty())
+    .or_else(internet_settings_proxy)
+}
+
+/// The proxy configured in the windows settings (what browsers use)
+#[cfg(windows)]
+fn internet_settings_proxy() -> Option<String> {
+    use windows::{
+        Win32::System::Registry::{
+            HKEY_CURRENT_USER, RRF_RT_REG_DWORD, RRF_RT_REG_SZ, RegGetValueW,
+        },
+        core::w,
+    };
+    const KEY: windows::core::PCWSTR =
+        w!(r"Software\Microsoft\Windows\CurrentVersion\Internet Settings");
+
+    let mut enabled = 0u32;
+    let mut size = size_of::<u32>() as u32;
+    let res = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            KEY,
+            w!("ProxyEnable"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut enabled as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    };
+    if res.is_err() || enabled == 0 {
+        return None;
+    }
+
+    let mut buf = [0u16; 512];
+    let mut size = size_of_val(&buf) as u32;
+    let res = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            KEY,
+            w!("ProxyServer"),
+            RRF_RT_REG_SZ,
+            None,
+            Some(buf.as_mut_ptr() as *mut _),
+            Some(&mut size),
+        )
+    };
+    if res.is_err() {
+        return None;
+    }
+    let len = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+    parse_proxy_server(&String::from_utf16_lossy(&buf[..len]))
+}
+
+#[cfg(not(windows))]
+fn internet_settings_proxy() -> Option<String> {
+    None
+}
+
+/// Either `host:port` for all protocols or `http=host:port;https=host:port;socks=host:port`
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_proxy_server(value: &str) -> Option<String> {
+    let server = if value.contains('=') {
+        let per_protocol = |protocol: &str| {
+            value
+                .split(';')
+                .filter_map(|entry| entry.split_once('='))
+                .find(|(p, _)| p.trim().eq_ignore_ascii_case(protocol))
+                .map(|(_, server)| server.trim())
+        };
+        match per_protocol("https").or_else(|| per_protocol("http")) {
+            Some(server) => format!("http://{server}"),
+            None => format!("socks5://{}", per_protocol("socks")?),
+        }
+    } else {
+        value.trim().to_string()
+    };
+    if server.is_empty() {
+        None
+    } else if server.contains("://") {
+        Some(server)
+    } else {
+        Some(format!("http://{server}"))
+    }
+}
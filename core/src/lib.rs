@@ -0,0 +1,25 @@
+//! Everything of the log uploader that doesn't depend on the game: watching the log directory,
+//! parsing the logs and uploading them. Used by the Nexus addon and the CLI.
+
+pub mod arcdpslog;
+pub mod common;
+pub mod connectivity;
+pub mod dpsreport;
+pub mod eijson;
+pub mod evtc;
+pub mod filewatcher;
+pub mod history;
+pub mod http;
+pub mod multipart;
+pub mod notifications;
+pub mod profile;
+pub mod schedule;
+pub mod session;
+pub mod settings;
+pub mod summary;
+pub mod sync;
+pub mod table;
+pub mod theme;
+pub mod uploader;
+pub mod util;
+pub mod wingman;
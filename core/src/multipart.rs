@@ -0,0 +1,141 @@
+use std::{
+    fs::File,
+    io::{self, Cursor, Read},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Bytes sent of a request body
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub sent: u64,
+    pub total: u64,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.sent as f32 / self.total as f32
+    }
+}
+
+/// A `multipart/form-data` body which streams the file instead of loading it into memory
+pub struct Multipart {
+    boundary: String,
+    head: Vec<u8>,
+}
+
+impl Multipart {
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Self {
+            boundary: format!("----LogUploaderBoundary{nanos:x}"),
+            head: vec![],
+        }
+    }
+
+    pub fn text(mut self, name: &str, value: &str) -> Self {
+        self.head.extend(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n",
+                self.boundary
+            )
+            .as_bytes(),
+        );
+        self
+    }
+
+    /// Finishes the body with the file as last part
+    pub fn file(mut self, name: &str, path: &Path) -> io::Result<Body> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let filename = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .replace('"', "");
+        self.head.extend(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                self.boundary
+            )
+            .as_bytes(),
+        );
+        let tail = format!("\r\n--{}--\r\n", self.boundary).into_bytes();
+        Ok(Body {
+            content_type: format!("multipart/form-data; boundary={}", self.boundary),
+            len: self.head.len() as u64 + file_len + tail.len() as u64,
+            reader: Box::new(Cursor::new(self.head).chain(file).chain(Cursor::new(tail))),
+        })
+    }
+}
+
+impl Default for Multipart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Body {
+    pub content_type: String,
+    pub len: u64,
+    reader: Box<dyn Read + Send>,
+}
+
+impl Body {
+    /// Reports the bytes read every now and then and fails the read once `cancel` is set
+    pub fn with_progress(
+        self,
+        cancel: Arc<AtomicBool>,
+        on_progress: impl FnMut(Progress) + Send + 'static,
+    ) -> ProgressReader {
+        ProgressReader {
+            inner: self.reader,
+            progress: Progress {
+                sent: 0,
+                total: self.len,
+            },
+            cancel,
+            on_progress: Box::new(on_progress),
+            last_report: None,
+        }
+    }
+}
+
+// Often enough for a smooth progress bar without flooding the channel
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct ProgressReader {
+    inner: Box<dyn Read + Send>,
+    progress: Progress,
+    cancel: Arc<AtomicBool>,
+    on_progress: Box<dyn FnMut(Progress) + Send>,
+    last_report: Option<Instant>,
+}
+
+impl Read for ProgressReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.cancel.load(Ordering::Relaxed) {
+            return Err(io::Error::other("Upload cancelled"));
+        }
+        let n = self.inner.read(buf)?;
+        self.progress.sent += n as u64;
+        let due = self
+            .last_report
+            .is_none_or(|t| t.elapsed() >= REPORT_INTERVAL);
+        if n > 0 && (due || self.progress.sent == self.progress.total) {
+            self.last_report = Some(Instant::now());
+            (self.on_progress)(self.progress);
+        }
+        Ok(n)
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Which events the front end should notify the user about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Notifications {
+    pub kill: bool,
+    pub upload_failed: bool,
+    pub rate_limited: bool,
+    pub watcher_lost: bool,
+    /// Going offline and coming back online
+    pub connection: bool,
+}
+
+impl Notifications {
+    pub const fn default() -> Self {
+        Self {
+            kill: true,
+            upload_failed: true,
+            rate_limited: false,
+            watcher_lost: true,
+            connection: true,
+        }
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self::default()
+    }
+}
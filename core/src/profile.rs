@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    summary::{self, Category},
+    uploader::Target,
+};
+
+fn default_true() -> bool {
+    true
+}
+
+pub(crate) fn default_copyformat() -> String {
+    String::from("@1")
+}
+
+/// Everything that decides where a log goes. The settings hold the default profile, named ones
+/// can be switched to or picked automatically per log.
+// serde defaults only for the case, the file exists, but doesnt contain all the fields
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub dpsreport_token: String,
+    #[serde(default = "default_copyformat")]
+    pub dpsreport_copyformat: String,
+    #[serde(default = "default_true")]
+    pub enable_dpsreport: bool,
+    #[serde(default = "default_true")]
+    pub enable_wingman: bool,
+    #[serde(default)]
+    pub filter_dpsreport: Vec<u16>,
+    #[serde(default)]
+    pub filter_wingman: Vec<u16>,
+}
+
+impl Profile {
+    pub const fn default() -> Self {
+        Self {
+            dpsreport_token: String::new(),
+            // Cannot use default_copyformat() because it's not const
+            dpsreport_copyformat: String::new(),
+            enable_dpsreport: true,
+            enable_wingman: true,
+            filter_dpsreport: Vec::new(),
+            filter_wingman: Vec::new(),
+        }
+    }
+
+    /// Whether logs of `boss_id` go to `target`. Wingman doesn't take WvW logs.
+    pub fn uploads_to(&self, target: Target, boss_id: u16) -> bool {
+        match target {
+            Target::DpsReport => self.enable_dpsreport && !self.filter_dpsreport.contains(&boss_id),
+            Target::Wingman => {
+                self.enable_wingman && boss_id != 1 && !self.filter_wingman.contains(&boss_id)
+            }
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+/// A profile the user created, with the rules for picking it automatically
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedProfile {
+    pub name: String,
+    #[serde(flatten)]
+    pub profile: Profile,
+    /// Logs recorded by one of these accounts use this profile
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// Logs of bosses in one of these categories use this profile
+    #[serde(default)]
+    pub categories: Vec<Category>,
+}
+
+impl NamedProfile {
+    pub fn new(name: String, profile: Profile) -> Self {
+        Self {
+            name,
+            profile,
+            accounts: Vec::new(),
+            categories: Vec::new(),
+        }
+    }
+
+    /// Whether a log of `boss_id` recorded by `pov` should use this profile
+    pub fn matches(&self, pov: Option<&str>, boss_id: u16) -> bool {
+        let account = |a: &str| a.trim().trim_start_matches(':').to_lowercase();
+        let pov_matches = pov.is_some_and(|pov| {
+            let pov = account(pov);
+            self.accounts.iter().any(|a| account(a) == pov)
+        });
+        pov_matches || self.categories.contains(&summary::category(boss_id).0)
+    }
+}
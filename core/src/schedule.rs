@@ -0,0 +1,65 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::uploader::Target;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Defer {
+    /// Upload as soon as the log is parsed
+    Never,
+    /// Until no new log appeared for [`Schedule::idle_secs`]
+    Idle,
+    /// Until the player left combat, as reported by arcdps
+    OutOfCombat,
+}
+
+impl Defer {
+    pub const ALL: [Self; 3] = [Self::Never, Self::Idle, Self::OutOfCombat];
+}
+
+/// When uploads are started. Uploading during a fight can cause stutters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Schedule {
+    pub defer: Defer,
+    pub idle_secs: u64,
+    /// Which targets are held back, the other ones upload right away
+    pub dpsreport: bool,
+    pub wingman: bool,
+}
+
+impl Schedule {
+    pub const fn default() -> Self {
+        Self {
+            defer: Defer::Never,
+            idle_secs: 120,
+            dpsreport: true,
+            wingman: true,
+        }
+    }
+
+    /// Whether uploads to `target` have to wait right now
+    pub fn holds(&self, target: Target, last_new_log: Option<Instant>, in_combat: bool) -> bool {
+        let opted_in = match target {
+            Target::DpsReport => self.dpsreport,
+            Target::Wingman => self.wingman,
+        };
+        if !opted_in {
+            return false;
+        }
+        match self.defer {
+            Defer::Never => false,
+            Defer::Idle => {
+                last_new_log.is_some_and(|t| t.elapsed() < Duration::from_secs(self.idle_secs))
+            }
+            Defer::OutOfCombat => in_combat,
+        }
+    }
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::default()
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    ops::Range,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    arcdpslog::{Log, format_time},
+    summary::format_duration,
+    util::e_fill,
+};
+
+/// A block of logs without a larger break than the configured gap between them.
+/// Start and end are encounter end times.
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Indices into the log list
+    pub logs: Range<usize>,
+    pub start: Option<SystemTime>,
+    pub end: Option<SystemTime>,
+    pub kills: usize,
+    pub wipes: usize,
+}
+
+impl Session {
+    fn new(start: usize) -> Self {
+        Self {
+            logs: start..start,
+            start: None,
+            end: None,
+            kills: 0,
+            wipes: 0,
+        }
+    }
+
+    fn push(&mut self, log: &Log) {
+        self.logs.end += 1;
+        if let Some(t) = log.end_time() {
+            self.start = Some(self.start.map_or(t, |s| s.min(t)));
+            self.end = Some(self.end.map_or(t, |e| e.max(t)));
+        }
+        match log.success() {
+            Some(true) => self.kills += 1,
+            Some(false) => self.wipes += 1,
+            None => {}
+        }
+    }
+
+    /// Time between the end of the first and the end of the last encounter
+    pub fn duration(&self) -> Duration {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => end.duration_since(start).unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    pub fn label(&self, index: usize) -> String {
+        e_fill(
+            "Session {} ({}): {} kills, {} wipes, {}",
+            &[
+                &(index + 1),
+                &self.start.map(format_time).unwrap_or_default(),
+                &self.kills,
+                &self.wipes,
+                &format_duration(self.duration().as_secs()),
+            ],
+        )
+    }
+}
+
+/// Splits the logs into sessions whenever the time between two consecutive encounter ends is
+/// larger than `gap`. Logs without a known end time are added to the current session.
+pub fn split(logs: &[Log], gap: Duration) -> Vec<Session> {
+    let mut sessions: Vec<Session> = vec![];
+    let mut last: Option<SystemTime> = None;
+    for (i, l) in logs.iter().enumerate() {
+        let end = l.end_time();
+        let new_session = match (last, end) {
+            (Some(last), Some(t)) => t.duration_since(last).unwrap_or_default() > gap,
+            _ => sessions.is_empty(),
+        };
+        if new_session {
+            sessions.push(Session::new(i));
+        }
+        sessions.last_mut().expect("session was created").push(l);
+        if end.is_some() {
+            last = end;
+        }
+    }
+    sessions
+}
@@ -0,0 +1,326 @@
+use std::{
+    fs::{File, create_dir_all},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use dirs_next::document_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arcdpslog::Approval,
+    dpsreport::{self, DpsReportOptions},
+    http::Http,
+    notifications::Notifications,
+    profile::{NamedProfile, Profile, default_copyformat},
+    schedule::Schedule,
+    table::{Columns, TimeFormat},
+    theme::Theme,
+    uploader::Target,
+    wingman,
+};
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_logs() -> usize {
+    200
+}
+
+fn default_time_format() -> TimeFormat {
+    TimeFormat::Hours24
+}
+
+fn default_session_gap() -> u64 {
+    30
+}
+
+/// Upload servers, only set when they differ from the public ones (self hosted wingman, tests)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Endpoints {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dpsreport: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wingman: Option<String>,
+}
+
+impl Endpoints {
+    pub const fn default() -> Self {
+        Self {
+            dpsreport: None,
+            wingman: None,
+        }
+    }
+
+    pub fn dpsreport(&self) -> &str {
+        self.dpsreport.as_deref().unwrap_or(dpsreport::DEFAULT_URL)
+    }
+
+    pub fn wingman(&self) -> &str {
+        self.wingman.as_deref().unwrap_or(wingman::DEFAULT_URL)
+    }
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+// serde defaults only for the case, the file exists, but doesnt contain all the fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    pub logpath: String,
+    /// Used unless another profile is active or picked automatically
+    #[serde(flatten)]
+    pub profile: Profile,
+    #[serde(default)]
+    pub profiles: Vec<NamedProfile>,
+    /// Name of the profile in use, empty for the default one
+    #[serde(default)]
+    pub active_profile: String,
+    /// Pick a named profile by the rules of the profiles, instead of using the active one
+    #[serde(default)]
+    pub auto_profile: bool,
+    #[serde(default)]
+    pub show_window: bool,
+    #[serde(default)]
+    pub show_statistics: bool,
+    /// Logs are still parsed but not uploaded
+    #[serde(default)]
+    pub paused: bool,
+    /// New logs from the log directory are only uploaded once the user approved them
+    #[serde(default)]
+    pub approval_mode: bool,
+    #[serde(default)]
+    pub rev_log_order: bool,
+    #[serde(default)]
+    pub columns: Columns,
+    #[serde(default = "default_time_format")]
+    pub time_format: TimeFormat,
+    #[serde(default)]
+    pub time_include_date: bool,
+    #[serde(default)]
+    pub theme: Theme,
+    /// Finished logs beyond this count are removed from the list (oldest first)
+    #[serde(default = "default_max_logs")]
+    pub max_logs: usize,
+    /// Finished logs older than this are removed from the list. 0 keeps them forever.
+    #[serde(default)]
+    pub max_log_age_hours: u64,
+    /// Minutes without a new log after which a new session is started
+    #[serde(default = "default_session_gap")]
+    pub session_gap_minutes: u64,
+    #[serde(default = "default_true")]
+    pub copy_success: bool,
+    #[serde(default = "default_true")]
+    pub copy_failure: bool,
+    #[serde(default)]
+    pub copy_summary: bool,
+    #[serde(default)]
+    pub notifications: Notifications,
+    #[serde(default)]
+    pub schedule: Schedule,
+    #[serde(default)]
+    pub http: Http,
+    #[serde(default)]
+    pub endpoints: Endpoints,
+    #[serde(default)]
+    pub dpsreport_options: DpsReportOptions,
+    /// Fetch the Elite Insights json of every log uploaded to dps.report, for the detail panel
+    #[serde(default)]
+    pub fetch_ei_json: bool,
+    #[serde(default)]
+    pub hide_hotfix_notification_20241114: bool,
+    #[serde(default)]
+    pub hotfix_20250512_executed: bool,
+}
+
+impl Settings {
+    const fn default() -> Self {
+        Self {
+            // Cannot use default_dir() because it's not consat
+            logpath: String::new(),
+            profile: Profile::default(),
+            profiles: Vec::new(),
+            active_profile: String::new(),
+            auto_profile: false,
+            show_window: true,
+            show_statistics: false,
+            paused: false,
+            approval_mode: false,
+            rev_log_order: false,
+            columns: Columns::default(),
+            time_format: TimeFormat::Hours24,
+            time_include_date: false,
+            theme: Theme::default(),
+            max_logs: 200,
+            max_log_age_hours: 0,
+            session_gap_minutes: 30,
+            copy_success: true,
+            copy_failure: true,
+            copy_summary: false,
+            notifications: Notifications::default(),
+            schedule: Schedule::default(),
+            http: Http::default(),
+            endpoints: Endpoints::default(),
+            dpsreport_options: DpsReportOptions::default(),
+            fetch_ei_json: false,
+            hide_hotfix_notification_20241114: false,
+            hotfix_20250512_executed: false,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.logpath = Self::default_dir().display().to_string();
+        self.profile.dpsreport_copyformat = default_copyformat();
+    }
+
+    pub fn get() -> MutexGuard<'static, Self> {
+        SETTINGS.lock().unwrap()
+    }
+    pub fn get_mut() -> MutexGuard<'static, Self> {
+        SETTINGS.lock().unwrap()
+    }
+
+    pub fn default_dir() -> PathBuf {
+        let mut base = document_dir().unwrap_or_default();
+        base.push("Guild Wars 2");
+        base.push("addons");
+        base.push("arcdps");
+        base.push("arcdps.cbtlogs");
+        base
+    }
+
+    // Default was an empty string if config file did not exist yet
+    // If the file existed (even if empty) it worked correctly
+    fn check_hotfix20250512(&self) -> bool {
+        self.profile.dpsreport_copyformat.is_empty()
+    }
+
+    pub fn fix_hotfix20250512(&mut self) {
+        // Only do this once so if someone actually uses an empty copyformat we wont overwrite it
+        // next restart
+        if !self.hotfix_20250512_executed && self.check_hotfix20250512() {
+            log::info!("Empty copyformat detected, setting default (Hotfix 20250512)");
+            self.profile.dpsreport_copyformat = default_copyformat();
+        }
+        // Always set this to true so we don't run this again
+        self.hotfix_20250512_executed = true;
+    }
+
+    pub fn check_hotfix20241114(&self) -> bool {
+        self.logpath.ends_with("arcdps.cbtlog")
+    }
+
+    pub fn fix_hotfix20241114(&mut self) {
+        self.logpath = Settings::default_dir().to_string_lossy().to_string();
+    }
+
+    pub fn max_log_age(&self) -> Option<Duration> {
+        (self.max_log_age_hours > 0).then(|| Duration::from_secs(self.max_log_age_hours * 3600))
+    }
+
+    pub fn session_gap(&self) -> Duration {
+        Duration::from_secs(self.session_gap_minutes * 60)
+    }
+
+    /// The profile picked in the window, falls back to the default one if it was deleted
+    pub fn active(&self) -> &Profile {
+        self.profile(&self.active_profile)
+    }
+
+    pub fn active_mut(&mut self) -> &mut Profile {
+        let name = self.active_profile.clone();
+        self.profile_mut(&name)
+    }
+
+    /// The profile called `name`, the default one for an empty or unknown name
+    pub fn profile(&self, name: &str) -> &Profile {
+        self.profiles
+            .iter()
+            .find(|p| p.name == name)
+            .map_or(&self.profile, |p| &p.profile)
+    }
+
+    pub fn profile_mut(&mut self, name: &str) -> &mut Profile {
+        match self.profiles.iter_mut().find(|p| p.name == name) {
+            Some(p) => &mut p.profile,
+            None => &mut self.profile,
+        }
+    }
+
+    /// Name and profile a log of `boss_id` recorded by `pov` is uploaded with. With automatic
+    /// selection the first matching profile wins, the active one if none matches.
+    pub fn profile_for(&self, pov: Option<&str>, boss_id: u16) -> (&str, &Profile) {
+        self.profiles
+            .iter()
+            .find(|p| self.auto_profile && p.matches(pov, boss_id))
+            .map_or((&self.active_profile, self.active()), |p| {
+                (&p.name, &p.profile)
+            })
+    }
+
+    /// Targets the profile of a parsed log uploads to. A log waiting for approval starts with
+    /// these, the others can't be picked.
+    pub fn approval(&self, pov: Option<&str>, boss_id: u16) -> Approval {
+        let (_, profile) = self.profile_for(pov, boss_id);
+        Approval {
+            dpsreport: profile.uploads_to(Target::DpsReport, boss_id),
+            wingman: profile.uploads_to(Target::Wingman, boss_id),
+        }
+    }
+
+    pub fn logpath(&self) -> &str {
+        &self.logpath
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if path.exists() {
+            let contents = std::fs::read_to_string(path)?;
+            let mut settings: Self = serde_json::from_str(&contents)?;
+            settings.fix_hotfix20250512();
+            *SETTINGS.lock().unwrap() = settings;
+        } else {
+            // Need to set here because it's not const
+            let mut settings = SETTINGS.lock().unwrap();
+            settings.init();
+        }
+        Ok(())
+    }
+
+    /// Sets the dps.report token of `profile` in the file, everything else stays as it is
+    /// stored. The CLI changes the settings in memory, those must not end up in the file.
+    pub fn store_token(path: impl AsRef<Path>, profile: &str, token: &str) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let mut settings = if path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(path)?)?
+        } else {
+            let mut settings = Self::default();
+            settings.init();
+            settings
+        };
+        settings.profile_mut(profile).dpsreport_token = token.to_string();
+        settings.store(path)
+    }
+
+    pub fn store(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let prefix = path.parent().unwrap();
+        create_dir_all(prefix)?;
+        let mut file = File::options()
+            .write(true)
+            .append(false)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        Ok(())
+    }
+}
+
+static SETTINGS: Mutex<Settings> = Mutex::new(Settings::default());
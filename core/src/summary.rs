@@ -0,0 +1,338 @@
+use std::{collections::HashMap, fmt};
+
+use revtc::bossdata::BossId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arcdpslog::{Log, Step},
+    dpsreport::DpsReportResponse,
+    settings::Settings,
+    util::{e, e_fill},
+};
+
+/// Groups encounters are listed under in the summary, in the order they are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Category {
+    Wing(u8),
+    IcebroodSaga,
+    EndOfDragons,
+    SecretsOfTheObscure,
+    Fractal,
+    WvW,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Self; 14] = [
+        Self::Wing(1),
+        Self::Wing(2),
+        Self::Wing(3),
+        Self::Wing(4),
+        Self::Wing(5),
+        Self::Wing(6),
+        Self::Wing(7),
+        Self::Wing(8),
+        Self::IcebroodSaga,
+        Self::EndOfDragons,
+        Self::SecretsOfTheObscure,
+        Self::Fractal,
+        Self::WvW,
+        Self::Other,
+    ];
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Category::Wing(1) => f.write_str(&e("Wing 1 - Spirit Vale")),
+            Category::Wing(2) => f.write_str(&e("Wing 2 - Salvation Pass")),
+            Category::Wing(3) => f.write_str(&e("Wing 3 - Stronghold of the Faithful")),
+            Category::Wing(4) => f.write_str(&e("Wing 4 - Bastion of the Penitent")),
+            Category::Wing(5) => f.write_str(&e("Wing 5 - Hall of Chains")),
+            Category::Wing(6) => f.write_str(&e("Wing 6 - Mythwright Gambit")),
+            Category::Wing(7) => f.write_str(&e("Wing 7 - The Key of Ahdashim")),
+            Category::Wing(8) => f.write_str(&e("Wing 8 - Mount Balrior")),
+            Category::Wing(n) => f.write_str(&e_fill("Wing {}", &[n])),
+            Category::IcebroodSaga => f.write_str(&e("Icebrood Saga Strikes")),
+            Category::EndOfDragons => f.write_str(&e("End of Dragons Strikes")),
+            Category::SecretsOfTheObscure => f.write_str(&e("Secrets of the Obscure Strikes")),
+            Category::Fractal => f.write_str(&e("Fractals of the Mists")),
+            Category::WvW => f.write_str(&e("World vs. World")),
+            Category::Other => f.write_str(&e("Other")),
+        }
+    }
+}
+
+// Header boss ids in wing order. The position in this table is used to sort encounters within
+// their category. Trigger ids missing here are looked up by their BossId.
+const ENCOUNTERS: &[(u16, Category)] = &[
+    // Spirit Vale
+    (15438, Category::Wing(1)), // Vale Guardian
+    (15429, Category::Wing(1)), // Gorseval
+    (15375, Category::Wing(1)), // Sabetha
+    // Salvation Pass
+    (16123, Category::Wing(2)), // Slothasor
+    (16088, Category::Wing(2)), // Bandit Trio
+    (16115, Category::Wing(2)), // Matthias
+    // Stronghold of the Faithful
+    (16253, Category::Wing(3)), // Escort
+    (16235, Category::Wing(3)), // Keep Construct
+    (16247, Category::Wing(3)), // Twisted Castle
+    (16246, Category::Wing(3)), // Xera
+    (16286, Category::Wing(3)), // Xera (second phase)
+    // Bastion of the Penitent
+    (17194, Category::Wing(4)), // Cairn
+    (17172, Category::Wing(4)), // Mursaat Overseer
+    (17188, Category::Wing(4)), // Samarog
+    (17154, Category::Wing(4)), // Deimos
+    // Hall of Chains
+    (19767, Category::Wing(5)), // Soulless Horror
+    (19828, Category::Wing(5)), // River of Souls
+    (19691, Category::Wing(5)), // Broken King
+    (19536, Category::Wing(5)), // Eater of Souls
+    (19651, Category::Wing(5)), // Eye of Judgment
+    (19844, Category::Wing(5)), // Eye of Fate
+    (19450, Category::Wing(5)), // Dhuum
+    // Mythwright Gambit
+    (43974, Category::Wing(6)), // Conjured Amalgamate
+    (21105, Category::Wing(6)), // Twin Largos (Nikare)
+    (21089, Category::Wing(6)), // Twin Largos (Kenut)
+    (20934, Category::Wing(6)), // Qadim
+    // The Key of Ahdashim
+    (22006, Category::Wing(7)), // Cardinal Adina
+    (21964, Category::Wing(7)), // Cardinal Sabir
+    (22000, Category::Wing(7)), // Qadim the Peerless
+    // Mount Balrior
+    (26725, Category::Wing(8)), // Greer
+    (26774, Category::Wing(8)), // Decima
+    (26712, Category::Wing(8)), // Ura
+    // Icebrood Saga
+    (21333, Category::IcebroodSaga), // Freezie
+    (22154, Category::IcebroodSaga), // Shiverpeaks Pass
+    (22343, Category::IcebroodSaga), // Voice and Claw
+    (22492, Category::IcebroodSaga), // Fraenir of Jormag
+    (22521, Category::IcebroodSaga), // Boneskinner
+    (22711, Category::IcebroodSaga), // Whisper of Jormag
+    (22836, Category::IcebroodSaga), // Cold War
+    // End of Dragons
+    (24033, Category::EndOfDragons), // Aetherblade Hideout
+    (23957, Category::EndOfDragons), // Xunlai Jade Junkyard
+    (24485, Category::EndOfDragons), // Kaineng Overlook
+    (24266, Category::EndOfDragons), // Kaineng Overlook (CM)
+    (43488, Category::EndOfDragons), // Harvest Temple
+    (25413, Category::EndOfDragons), // Old Lion's Court
+    // Secrets of the Obscure
+    (25705, Category::SecretsOfTheObscure), // Cosmic Observatory
+    (25989, Category::SecretsOfTheObscure), // Temple of Febe
+    // Fractals
+    (17021, Category::Fractal), // MAMA
+    (17028, Category::Fractal), // Siax
+    (16948, Category::Fractal), // Ensolyss
+    (17632, Category::Fractal), // Skorvald
+    (17949, Category::Fractal), // Artsariiv
+    (17759, Category::Fractal), // Arkk
+    (23254, Category::Fractal), // Ai, Keeper of the Peak
+    (25577, Category::Fractal), // Kanaxai
+    // arcdps uses 1 for all WvW logs
+    (1, Category::WvW),
+];
+
+/// Returns the category and the position within the clear order of a boss id.
+/// Unknown bosses (golems, new encounters) end up in [`Category::Other`].
+pub fn category(boss_id: u16) -> (Category, usize) {
+    ENCOUNTERS
+        .iter()
+        .position(|(id, _)| *id == boss_id)
+        .or_else(|| {
+            let boss = BossId::from_header_id(boss_id).to_string();
+            ENCOUNTERS
+                .iter()
+                .position(|(id, _)| BossId::from_header_id(*id).to_string() == boss)
+        })
+        .map(|i| (ENCOUNTERS[i].1, i))
+        .unwrap_or((Category::Other, ENCOUNTERS.len()))
+}
+
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}h {m:02}m {s:02}s")
+    } else {
+        format!("{m}m {s:02}s")
+    }
+}
+
+struct Attempt<'a> {
+    boss_id: u16,
+    // Bosses with multiple trigger ids (Xera, Twin Largos, ...) share the same BossId
+    boss: String,
+    dpsreport: &'a DpsReportResponse,
+}
+
+/// Wall clock time from the first pull to the end of the last attempt.
+/// Falls back to summing up the fight durations if dps.report didn't tell us when the
+/// encounters happened.
+fn clear_time(attempts: &[&Attempt]) -> u64 {
+    if attempts.iter().any(|a| a.dpsreport.encounter_time <= 0) {
+        return attempts
+            .iter()
+            .map(|a| a.dpsreport.encounter.duration)
+            .sum();
+    }
+    let start = attempts
+        .iter()
+        .map(|a| a.dpsreport.encounter_time)
+        .min()
+        .unwrap_or_default();
+    let end = attempts
+        .iter()
+        .map(|a| a.dpsreport.encounter_time + a.dpsreport.encounter.duration as i64)
+        .max()
+        .unwrap_or_default();
+    (end - start).max(0) as u64
+}
+
+/// Builds a raid night summary: one line per boss (the last kill, or the last attempt if the
+/// boss was never killed), grouped by wing/strike/fractal and sorted in clear order.
+pub fn collect_summary(logs: &[Log], settings: &Settings) -> String {
+    // The log list can be in reverse order, the latest attempts have to come last
+    let mut logs: Vec<_> = logs.iter().collect();
+    logs.sort_by_key(|l| l.end_time());
+    let mut groups: HashMap<Category, Vec<Attempt>> = HashMap::new();
+    for l in logs {
+        let (Step::Done(enc), Step::Done(dpsreport)) = (&l.evtc, &l.dpsreport) else {
+            continue;
+        };
+        let boss_id = enc.boss_id;
+        groups
+            .entry(category(boss_id).0)
+            .or_default()
+            .push(Attempt {
+                boss_id,
+                boss: BossId::from_header_id(boss_id).to_string(),
+                dpsreport,
+            });
+    }
+    let mut categories: Vec<_> = groups.keys().copied().collect();
+    categories.sort();
+
+    let mut lines = vec![];
+    let mut total = 0;
+    for c in categories {
+        let attempts = &groups[&c];
+        let mut bosses: Vec<(&str, usize)> = vec![];
+        for a in attempts {
+            if !bosses.iter().any(|(b, _)| *b == a.boss) {
+                bosses.push((&a.boss, category(a.boss_id).1));
+            }
+        }
+        bosses.sort_by_key(|(_, order)| *order);
+
+        let mut entries = vec![];
+        // The clear time covers the attempts of the bosses which are listed
+        let mut listed = vec![];
+        let mut kills = 0;
+        for (boss, _) in &bosses {
+            // logs are in chronological order, so the last matching attempt is the latest one
+            let of_boss = || attempts.iter().filter(|a| a.boss == *boss);
+            let best = of_boss()
+                .filter(|a| a.dpsreport.encounter.success)
+                .last()
+                .or_else(|| of_boss().last())
+                .expect("boss has at least one attempt");
+            let success = best.dpsreport.encounter.success;
+            if success {
+                kills += 1;
+            }
+            match (success, settings.copy_success, settings.copy_failure) {
+                (true, false, _) => continue,
+                (false, _, false) => continue,
+                _ => {
+                    entries.push(format_url(
+                        best.dpsreport,
+                        &settings.active().dpsreport_copyformat,
+                    ));
+                    listed.extend(of_boss());
+                }
+            }
+        }
+        if entries.is_empty() {
+            continue;
+        }
+        let time = clear_time(&listed);
+        total += time;
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "**{c}** ({})",
+            e_fill(
+                "{}/{} kills, {}",
+                &[&kills, &bosses.len(), &format_duration(time)]
+            )
+        ));
+        lines.extend(entries);
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+    lines.push(String::new());
+    lines.push(format!(
+        "**{}:** {}",
+        e("Total clear time"),
+        format_duration(total)
+    ));
+    lines.join("\r\n")
+}
+
+pub fn collect_urls(logs: &[Log], settings: &Settings) -> String {
+    if settings.copy_summary {
+        return collect_summary(logs, settings);
+    }
+    let mut urls = vec![];
+    for l in logs {
+        if let Step::Done(ref dpsreport) = l.dpsreport {
+            match (
+                dpsreport.encounter.success,
+                settings.copy_success,
+                settings.copy_failure,
+            ) {
+                (true, false, _) => continue,
+                (false, _, false) => continue,
+                _ => urls.push(format_url(
+                    dpsreport,
+                    &settings.active().dpsreport_copyformat,
+                )),
+            }
+        }
+    }
+    urls.join("\r\n")
+}
+
+pub fn format_url(dpsreport: &DpsReportResponse, format_template: &str) -> String {
+    /*
+        @1 - permalink
+        @2 - boss name & mode
+        @3 - boss id
+        @4 - encounter success/fail
+    */
+    let success = match dpsreport.encounter.success {
+        true => "Success",
+        false => "Fail",
+    };
+    let mode = dpsreport
+        .encounter
+        .format_mode()
+        .unwrap_or_else(|| "Unknown".to_string());
+    let cm = if mode == "" {
+        "".to_string()
+    } else {
+        format!(" ({})", mode)
+    };
+
+    return format_template
+        .replace("@1", &dpsreport.permalink)
+        .replace("@2", &format!("{}{}", dpsreport.encounter.boss, cm))
+        .replace("@3", &dpsreport.encounter.boss_id.to_string())
+        .replace("@4", &success);
+}
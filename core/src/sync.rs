@@ -0,0 +1,84 @@
+use std::{
+    sync::mpsc::{Receiver, Sender},
+    thread,
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::{common::WorkerMessage, dpsreport::DpsReportResponse, http};
+
+/// Logs whose start is this close to an upload of the same boss are the same log
+const MAX_TIME_DIFFERENCE: i64 = 5;
+
+/// Where the sync of the dps.report history is at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncState {
+    Idle,
+    Running,
+    /// Uploads dps.report knows about and how many of them were new to the history
+    Done {
+        uploads: usize,
+        imported: usize,
+    },
+    Failed(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadsPage {
+    pages: u32,
+    uploads: Vec<DpsReportResponse>,
+}
+
+/// Whether `upload` is the log of `boss_id` which started at `start` (unix timestamp)
+pub fn matches(upload: &DpsReportResponse, boss_id: u16, start: i64) -> bool {
+    upload.encounter.boss_id == boss_id as i64
+        && upload.encounter_time > 0
+        && (upload.encounter_time - start).abs() <= MAX_TIME_DIFFERENCE
+}
+
+/// Pages through every upload of `token`
+pub fn fetch_uploads(endpoint: &str, token: &str) -> anyhow::Result<Vec<DpsReportResponse>> {
+    if token.is_empty() {
+        anyhow::bail!("No dps.report token set");
+    }
+    let client = http::client().context("Invalid network settings")?;
+    let mut uploads = vec![];
+    let mut page = 1;
+    loop {
+        log::info!("[Sync] Fetching page {page}");
+        let res: UploadsPage = client
+            .get(&format!("{endpoint}/getUploads"))
+            .query("userToken", token)
+            .query("page", &page.to_string())
+            .call()
+            .map_err(|e| anyhow::anyhow!("{e}".replace(token, "******")))?
+            .into_json()
+            .context("Invalid response from dps.report")?;
+        uploads.extend(res.uploads);
+        if page >= res.pages {
+            return Ok(uploads);
+        }
+        page += 1;
+    }
+}
+
+/// Every job is a token to fetch the uploads of
+pub fn run(
+    inc: Receiver<String>,
+    out: Sender<WorkerMessage>,
+    endpoint: String,
+) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name("sync-thread".to_string())
+        .spawn(move || {
+            for token in inc {
+                let res = fetch_uploads(&endpoint, &token);
+                if let Err(e) = out.send(WorkerMessage::sync(res)) {
+                    log::error!("[Sync] Failed to send uploads to main thread: {e}");
+                }
+            }
+        })
+        .expect("Could not create sync thread")
+}
@@ -0,0 +1,134 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arcdpslog::Log,
+    util::{e, e_fill, e_or},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Encounter,
+    Start,
+    Duration,
+    Created,
+    DpsReport,
+    Wingman,
+    OpenInFolder,
+}
+
+/// Which of the optional columns are shown. The encounter column is always visible.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Columns {
+    pub start: bool,
+    pub duration: bool,
+    pub created: bool,
+    pub dpsreport: bool,
+    pub wingman: bool,
+    pub open_in_folder: bool,
+}
+
+impl Columns {
+    pub const fn default() -> Self {
+        Self {
+            start: true,
+            duration: true,
+            created: false,
+            dpsreport: true,
+            wingman: true,
+            open_in_folder: true,
+        }
+    }
+
+    pub fn visible(&self) -> Vec<Column> {
+        [
+            (Column::Encounter, true),
+            (Column::Start, self.start),
+            (Column::Duration, self.duration),
+            (Column::Created, self.created),
+            (Column::DpsReport, self.dpsreport),
+            (Column::Wingman, self.wingman),
+            (Column::OpenInFolder, self.open_in_folder),
+        ]
+        .into_iter()
+        .filter_map(|(c, visible)| visible.then_some(c))
+        .collect()
+    }
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeFormat {
+    /// 13:37
+    Hours24,
+    /// 01:37 PM
+    Hours12,
+    /// 5 min ago
+    Relative,
+}
+
+impl TimeFormat {
+    pub const ALL: [TimeFormat; 3] = [Self::Hours24, Self::Hours12, Self::Relative];
+
+    pub fn name(self) -> String {
+        match self {
+            Self::Hours24 => e("24 hours"),
+            Self::Hours12 => e("12 hours"),
+            Self::Relative => e("Relative"),
+        }
+    }
+
+    pub fn format(self, time: DateTime<Local>, include_date: bool) -> String {
+        let time_fmt = match self {
+            Self::Hours24 => "%R",
+            Self::Hours12 => "%I:%M %p",
+            Self::Relative => return format_relative(Local::now() - time),
+        };
+        if include_date {
+            let date_fmt = e_or("DATE_FORMAT", "%a %b %d");
+            time.format(&format!("{time_fmt} ({date_fmt})")).to_string()
+        } else {
+            time.format(time_fmt).to_string()
+        }
+    }
+}
+
+fn format_relative(delta: TimeDelta) -> String {
+    match delta.num_minutes() {
+        ..1 => e("just now"),
+        m @ 1..60 => e_fill("{} min ago", &[&m]),
+        m @ 60..1440 => e_fill("{} h ago", &[&(m / 60)]),
+        m => e_fill("{} days ago", &[&(m / 1440)]),
+    }
+}
+
+/// Column the table is currently sorted by. Without one, logs are shown in the order they were
+/// created.
+#[derive(Debug, Clone, Copy)]
+pub struct Sort {
+    pub column: Column,
+    pub ascending: bool,
+}
+
+impl Sort {
+    pub fn compare(&self, a: &Log, b: &Log) -> Ordering {
+        let ord = match self.column {
+            Column::Encounter => a.boss_name().cmp(&b.boss_name()),
+            Column::Start => a.start_time().cmp(&b.start_time()),
+            Column::Duration => a.duration().cmp(&b.duration()),
+            Column::Created => a.modified.cmp(&b.modified),
+            Column::DpsReport => a.dpsreport.rank().cmp(&b.dpsreport.rank()),
+            Column::Wingman => a.wingman.rank().cmp(&b.wingman.rank()),
+            Column::OpenInFolder => Ordering::Equal,
+        };
+        if self.ascending { ord } else { ord.reverse() }
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::e;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    Default,
+    /// Blue/orange, distinguishable with deuteranopia and protanopia
+    ColorBlind,
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Self::Default, Self::ColorBlind, Self::HighContrast];
+
+    pub fn name(self) -> String {
+        match self {
+            Self::Default => e("Default"),
+            Self::ColorBlind => e("Colour-blind safe"),
+            Self::HighContrast => e("High contrast"),
+        }
+    }
+
+    fn success(self) -> [f32; 4] {
+        match self {
+            Self::Default => [0.0, 1.0, 0.0, 1.0],
+            Self::ColorBlind => [0.34, 0.71, 0.91, 1.0],
+            Self::HighContrast => [0.4, 1.0, 1.0, 1.0],
+        }
+    }
+
+    fn failure(self) -> [f32; 4] {
+        match self {
+            Self::Default => [1.0, 0.0, 0.0, 1.0],
+            Self::ColorBlind => [0.9, 0.62, 0.0, 1.0],
+            Self::HighContrast => [1.0, 0.85, 0.0, 1.0],
+        }
+    }
+}
+
+/// How success and failure are shown. Glyphs are for everyone who can't rely on the colours.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub palette: Palette,
+    pub glyphs: bool,
+}
+
+impl Theme {
+    pub const fn default() -> Self {
+        Self {
+            palette: Palette::Default,
+            glyphs: false,
+        }
+    }
+
+    pub fn success(&self) -> [f32; 4] {
+        self.palette.success()
+    }
+
+    pub fn failure(&self) -> [f32; 4] {
+        self.palette.failure()
+    }
+
+    pub fn color(&self, success: bool) -> [f32; 4] {
+        if success {
+            self.success()
+        } else {
+            self.failure()
+        }
+    }
+
+    /// Faded failure colour for icon tints
+    pub fn failure_tint(&self) -> [f32; 4] {
+        let mut color = self.failure();
+        color[3] = 0.3;
+        color
+    }
+
+    pub fn glyph(&self, success: bool) -> &'static str {
+        match (self.glyphs, success) {
+            (false, _) => "",
+            (true, true) => "+",
+            (true, false) => "x",
+        }
+    }
+
+    /// `text` prefixed with the status glyph if enabled
+    pub fn label(&self, success: bool, text: impl AsRef<str>) -> String {
+        if self.glyphs {
+            format!("{} {}", self.glyph(success), text.as_ref())
+        } else {
+            text.as_ref().to_string()
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::default()
+    }
+}
@@ -0,0 +1,744 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use notify::{ErrorKind, Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    arcdpslog::{Approval, Log, Step},
+    common::{WorkerMessage, WorkerType},
+    connectivity,
+    dpsreport::{self, DpsReportResponse, RetryReason},
+    eijson, evtc,
+    filewatcher::ReceiverExt,
+    history,
+    settings::Settings,
+    sync::{self, SyncState},
+    util::e,
+    wingman,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    DpsReport,
+    Wingman,
+}
+
+impl Target {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DpsReport => "dps.report",
+            Self::Wingman => "Wingman",
+        }
+    }
+}
+
+/// Things the front end might want to tell the user (or other addons) about
+#[derive(Debug)]
+pub enum UploaderEvent<'a> {
+    /// The evtc of a new log was parsed
+    Parsed(&'a Log),
+    /// A target accepted the log
+    Uploaded(Target, &'a Log),
+    /// A target gave up on the log (after all retries)
+    Failed(Target, &'a Log),
+    /// dps.report asked us to slow down, the log is retried later
+    RateLimited,
+    /// The log directory can't be watched anymore
+    WatcherLost(&'a str),
+    /// An upload couldn't reach its server, uploads wait until the connection is back
+    Offline,
+    /// The connection is back, the queued logs are uploaded
+    Online,
+}
+
+/// Watches the log directory and moves every log through parsing and the uploads.
+/// Owns the worker threads, call [`Uploader::shutdown`] to stop them.
+pub struct Uploader {
+    producer_rx: Receiver<WorkerMessage>,
+    evtc_tx: Sender<evtc::EvtcJob>,
+    dps_tx: Sender<dpsreport::DpsJob>,
+    wingman_tx: Sender<wingman::WingmanJob>,
+    ei_tx: Sender<eijson::EiJob>,
+    watcher: Box<dyn Watcher + Send>,
+    file_rx: Receiver<Result<Event, notify::Error>>,
+    threads: Vec<thread::JoinHandle<()>>,
+    pub logs: Vec<Log>,
+    settings_path: PathBuf,
+    history_path: PathBuf,
+    watching: bool,
+    last_check: Option<Instant>,
+    path_ok: bool,
+    /// When the last log was added, for [`crate::schedule::Defer::Idle`]
+    last_new_log: Option<Instant>,
+    in_combat: bool,
+    /// Set by us, cleared by the connectivity thread once a server answers again
+    offline: Arc<connectivity::Offline>,
+    was_offline: bool,
+    probe_tx: Sender<()>,
+    sync_tx: Sender<String>,
+    /// Uploads of the token fetched by the last sync, logs matching one aren't uploaded again
+    synced: Vec<DpsReportResponse>,
+    /// Profile whose token the last sync used, only its logs are matched against the uploads
+    synced_profile: String,
+    pub sync: SyncState,
+}
+
+impl Uploader {
+    /// Without a `logpath` only enqueued logs are uploaded. `poll` falls back to polling the log
+    /// directory, for systems where the native watcher is unreliable (wine)
+    pub fn new(
+        logpath: Option<&Path>,
+        poll: bool,
+        settings_path: PathBuf,
+        history_path: PathBuf,
+    ) -> Self {
+        let (producer_tx, producer_rx) = mpsc::channel();
+        let (evtc_tx, evtc_rx) = mpsc::channel();
+        let (dps_tx, dps_rx) = mpsc::channel();
+        let (wingman_tx, wingman_rx) = mpsc::channel();
+        let endpoints = Settings::get().endpoints.clone();
+        let offline = Arc::new(connectivity::Offline::default());
+        let (probe_tx, probe_rx) = mpsc::channel();
+        let (sync_tx, sync_rx) = mpsc::channel();
+        let (ei_tx, ei_rx) = mpsc::channel();
+        // Next to the settings, so the CLI and the tests keep it out of the game directory
+        let ei_cache = settings_path.with_file_name("eijson");
+        let threads = vec![
+            evtc::run(evtc_rx, producer_tx.clone()),
+            dpsreport::run(
+                dps_rx,
+                producer_tx.clone(),
+                endpoints.dpsreport().to_string(),
+            ),
+            sync::run(
+                sync_rx,
+                producer_tx.clone(),
+                endpoints.dpsreport().to_string(),
+            ),
+            eijson::run(
+                ei_rx,
+                producer_tx.clone(),
+                endpoints.dpsreport().to_string(),
+                ei_cache,
+            ),
+            wingman::run(wingman_rx, producer_tx, endpoints.wingman().to_string()),
+            connectivity::run(
+                probe_rx,
+                offline.clone(),
+                vec![
+                    (Target::DpsReport, endpoints.dpsreport().to_string()),
+                    (Target::Wingman, endpoints.wingman().to_string()),
+                ],
+            ),
+        ];
+
+        let (tx, file_rx) = mpsc::channel();
+        // unwrap this, this can only fail, if creating the semaphore fails
+        let mut watcher: Box<dyn Watcher + Send> = if poll {
+            Box::new(
+                PollWatcher::new(
+                    tx,
+                    notify::Config::default().with_poll_interval(Duration::from_secs(5)),
+                )
+                .unwrap(),
+            )
+        } else {
+            Box::new(RecommendedWatcher::new(tx, notify::Config::default()).unwrap())
+        };
+        match logpath {
+            Some(logpath) if logpath.is_dir() => {
+                if let Err(e) = watcher.watch(logpath, RecursiveMode::Recursive) {
+                    log::error!("Failed to watch {}: {e}", logpath.display());
+                }
+            }
+            Some(logpath) => log::warn!(
+                "Could not watch directory. {} does not exist or is not a directory",
+                logpath.display()
+            ),
+            None => {}
+        }
+
+        Self {
+            producer_rx,
+            evtc_tx,
+            dps_tx,
+            wingman_tx,
+            ei_tx,
+            watcher,
+            file_rx,
+            threads,
+            logs: vec![],
+            settings_path,
+            history_path,
+            watching: logpath.is_some(),
+            last_check: None,
+            path_ok: true,
+            last_new_log: None,
+            in_combat: false,
+            offline,
+            was_offline: false,
+            probe_tx,
+            sync_tx,
+            synced: vec![],
+            synced_profile: String::new(),
+            sync: SyncState::Idle,
+        }
+    }
+
+    pub fn watch(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if let Err(e) = self.watcher.watch(path, RecursiveMode::Recursive) {
+            log::error!("Failed to watch {}: {e}", path.display());
+        }
+    }
+
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if let Err(e) = self.watcher.unwatch(path) {
+            log::error!("Failed to unwatch {}: {e}", path.display());
+        }
+    }
+
+    /// Adds a log which didn't come from the watched directory. Those were explicitly asked for,
+    /// so they don't wait for approval.
+    pub fn enqueue(&mut self, path: PathBuf) {
+        if !path.is_file() {
+            log::warn!("Ignoring enqueued log {}: not a file", path.display());
+        } else if self.logs.iter().any(|l| l.location == path) {
+            log::info!(
+                "Ignoring enqueued log {}: already in the list",
+                path.display()
+            );
+        } else {
+            self.logs.push(Log::new(path));
+            self.last_new_log = Some(Instant::now());
+        }
+    }
+
+    /// Only the front end knows about the game. Never called by the CLI, so it never waits
+    /// for combat to end.
+    pub fn set_in_combat(&mut self, in_combat: bool) {
+        if self.in_combat != in_combat {
+            log::debug!("In combat: {in_combat}");
+            self.in_combat = in_combat;
+        }
+    }
+
+    /// Fetches all uploads of the dps.report token, adds them to the history and marks the logs
+    /// of `profile` which were already uploaded from somewhere else. Takes the profile and its
+    /// token instead of reading the settings, the addon keeps them locked while rendering.
+    pub fn sync_history(&mut self, profile: &str, token: &str) {
+        if self.sync == SyncState::Running {
+            return;
+        }
+        match self.sync_tx.send(token.to_string()) {
+            Ok(()) => {
+                self.sync = SyncState::Running;
+                self.synced_profile = profile.to_string();
+            }
+            Err(e) => log::error!("Failed to send sync job: {e}"),
+        }
+    }
+
+    /// A server couldn't be reached, its uploads are held back without using up retries
+    pub fn is_offline(&self) -> bool {
+        self.offline.any()
+    }
+
+    /// Picks up new logs, hands them to the workers and collects the results.
+    /// Meant to be called regularly, e.g. once per frame.
+    pub fn tick(&mut self, mut handler: impl FnMut(UploaderEvent)) {
+        self.check_connectivity(&mut handler);
+        self.get_new_logs(&mut handler);
+        self.advance_logs(&mut handler);
+        self.update_logs(&mut handler);
+        self.record_history();
+        self.prune_logs();
+    }
+
+    /// Closes the channels and waits for the workers to finish their current job
+    pub fn shutdown(self) {
+        let Self {
+            producer_rx,
+            evtc_tx,
+            dps_tx,
+            wingman_tx,
+            watcher,
+            file_rx,
+            threads,
+            probe_tx,
+            sync_tx,
+            ei_tx,
+            ..
+        } = self;
+        log::trace!("Closing channels");
+        drop(probe_tx);
+        drop(sync_tx);
+        drop(ei_tx);
+        drop(watcher);
+        drop(file_rx);
+        drop(evtc_tx);
+        drop(dps_tx);
+        drop(wingman_tx);
+        drop(producer_rx);
+
+        log::trace!("Waiting on threads");
+        for t in threads {
+            let threadname = t
+                .thread()
+                .name()
+                .map(String::from)
+                .unwrap_or_else(|| format!("{:?}", t.thread().id()));
+            log::trace!("Waiting on thread {}", threadname);
+            if let Err(e) = t.join() {
+                log::error!("Failed to join thread {}: {:#?}", threadname, e);
+            }
+        }
+    }
+
+    fn get_new_logs(&mut self, handler: &mut impl FnMut(UploaderEvent)) {
+        self.check_logpath(handler);
+        let approval = Settings::get().approval_mode.then_some(Approval::ALL);
+        loop {
+            match self.file_rx.next_log() {
+                Ok(iter) => {
+                    for l in iter {
+                        log::info!("New log found: {}", l.display());
+                        let mut log = Log::new(l);
+                        log.approval = approval;
+                        self.logs.push(log);
+                        self.last_new_log = Some(Instant::now());
+                    }
+                }
+                // Empty queue or an event we don't care about
+                Err(e) if matches!(e.kind, ErrorKind::Generic(_)) => break,
+                Err(e) => {
+                    log::error!("Filewatcher error: {e}");
+                    let logpath = Settings::get().logpath().to_string();
+                    handler(UploaderEvent::WatcherLost(&logpath));
+                    break;
+                }
+            }
+        }
+    }
+
+    /// The watcher doesn't report the watched directory disappearing (e.g. a network drive or a
+    /// renamed folder) so check for it every few seconds.
+    fn check_logpath(&mut self, handler: &mut impl FnMut(UploaderEvent)) {
+        if !self.watching
+            || self
+                .last_check
+                .is_some_and(|t| t.elapsed() < Duration::from_secs(5))
+        {
+            return;
+        }
+        self.last_check = Some(Instant::now());
+        let logpath = Settings::get().logpath().to_string();
+        let ok = Path::new(&logpath).is_dir();
+        match (std::mem::replace(&mut self.path_ok, ok), ok) {
+            (true, false) => {
+                log::warn!("Log directory {logpath} is gone");
+                handler(UploaderEvent::WatcherLost(&logpath));
+            }
+            // The old watch died with the directory
+            (false, true) => self.watch(&logpath),
+            _ => {}
+        }
+    }
+
+    fn finish_sync(&mut self, uploads: Vec<DpsReportResponse>) {
+        let entries = uploads.iter().map(|upload| {
+            let local = self.logs.iter().find(|l| is_upload_of(upload, l));
+            history::Entry::from_upload(upload, local.map(|l| l.location.clone()))
+        });
+        let imported = history::record_all(entries, &self.history_path).unwrap_or_else(|e| {
+            log::error!("Failed to store upload history: {e}");
+            0
+        });
+        log::info!(
+            "Synced {} dps.report uploads, {imported} new",
+            uploads.len()
+        );
+        self.sync = SyncState::Done {
+            uploads: uploads.len(),
+            imported,
+        };
+        self.synced = uploads;
+        let settings = Settings::get();
+        for l in self.logs.iter_mut() {
+            if matches!(l.dpsreport, Step::Active | Step::Done(_))
+                || profile_of(l, &settings) != Some(self.synced_profile.as_str())
+            {
+                continue;
+            }
+            if let Some(upload) = find_synced(&self.synced, l) {
+                l.dpsreport = Step::Done(upload.clone());
+                if settings.fetch_ei_json {
+                    l.request_ei_json();
+                }
+            }
+        }
+    }
+
+    fn check_connectivity(&mut self, handler: &mut impl FnMut(UploaderEvent)) {
+        let offline = self.is_offline();
+        if self.was_offline && !offline {
+            log::info!("Back online, uploading the queued logs");
+            handler(UploaderEvent::Online);
+        }
+        self.was_offline = offline;
+    }
+
+    fn update_logs(&mut self, handler: &mut impl FnMut(UploaderEvent)) {
+        while let Ok(WorkerMessage { id, payload }) = self.producer_rx.try_recv() {
+            // The log might have been removed from the list while the worker was busy
+            let log = self.logs.iter_mut().find(|l| l.id == id);
+            match payload {
+                WorkerType::Evtc(evtc) => {
+                    let Some(log) = log else { continue };
+                    let evtc = evtc.map(|(enc, outcome)| {
+                        log.outcome = Some(outcome);
+                        enc
+                    });
+                    log.evtc = Step::from_value(evtc);
+                    if let Step::Done(enc) = &log.evtc {
+                        if let Some(approval) = &mut log.approval {
+                            let eligible =
+                                Settings::get().approval(enc.pov.as_deref(), enc.boss_id);
+                            *approval = eligible;
+                            log.eligible = eligible;
+                        }
+                        handler(UploaderEvent::Parsed(log));
+                    }
+                }
+                WorkerType::Sync(Ok(uploads)) => self.finish_sync(uploads),
+                WorkerType::Sync(Err(e)) => {
+                    log::error!("Failed to sync dps.report history: {e}");
+                    self.sync = SyncState::Failed(e.to_string());
+                }
+                WorkerType::Offline(target, reason) => {
+                    log::debug!("{} unreachable: {reason}", target.name());
+                    if let Some(log) = log {
+                        // Not an attempt, so the retry budget stays untouched
+                        match target {
+                            Target::DpsReport => {
+                                log.dpsreport = Step::Pending;
+                                log.dpsreport_progress = None;
+                            }
+                            Target::Wingman => {
+                                log.wingman = Step::Pending;
+                                log.wingman_progress = None;
+                            }
+                        }
+                    }
+                    let was_offline = self.is_offline();
+                    if !self.offline.set(target, true) {
+                        log::warn!(
+                            "{} unreachable, holding its uploads until back online",
+                            target.name()
+                        );
+                    }
+                    if !was_offline {
+                        self.was_offline = true;
+                        handler(UploaderEvent::Offline);
+                    }
+                }
+                WorkerType::Progress(target, progress) => {
+                    let Some(log) = log else { continue };
+                    match target {
+                        Target::DpsReport => log.dpsreport_progress = Some(progress),
+                        Target::Wingman => log.wingman_progress = Some(progress),
+                    }
+                }
+                WorkerType::DpsReport(profile, r) => match r {
+                    Ok(Ok(r)) => {
+                        keep_token(&self.settings_path, &profile, &r.user_token);
+                        if let Some(log) = log {
+                            log.dpsreport_progress = None;
+                            log.dpsreport = Step::from_value(Ok(r));
+                            if Settings::get().fetch_ei_json {
+                                log.request_ei_json();
+                            }
+                            handler(UploaderEvent::Uploaded(Target::DpsReport, log));
+                        }
+                    }
+                    Ok(Err(retry)) => {
+                        if retry.reason == RetryReason::RateLimited {
+                            handler(UploaderEvent::RateLimited);
+                        }
+                        if let Some(log) = log {
+                            log.dpsreport_progress = None;
+                            log.dpsreport = Step::Retry(retry.at);
+                        }
+                    }
+                    Err(e) => {
+                        if let Some(log) = log {
+                            log.dpsreport_progress = None;
+                            log.dpsreport = Step::from_value(Err(e));
+                            handler(UploaderEvent::Failed(Target::DpsReport, log));
+                        }
+                    }
+                },
+                WorkerType::EiJson(json) => {
+                    let Some(log) = log else { continue };
+                    if let Err(e) = &json {
+                        log::error!("Failed to fetch details of {}: {e}", log.location.display());
+                    }
+                    log.ei_json = Step::from_value(json);
+                }
+                WorkerType::Wingman(r) => {
+                    if let Some(log) = log {
+                        let uploaded = matches!(r, Ok(true));
+                        log.wingman_progress = None;
+                        log.wingman = Step::from_value(r);
+                        if uploaded {
+                            handler(UploaderEvent::Uploaded(Target::Wingman, log));
+                        } else {
+                            handler(UploaderEvent::Failed(Target::Wingman, log));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Only record once every target is done, so the entry contains the dps.report result if
+    // there is one
+    fn record_history(&mut self) {
+        for l in self
+            .logs
+            .iter_mut()
+            .filter(|l| !l.recorded && l.is_finished())
+        {
+            l.recorded = true;
+            if let Some(entry) = history::Entry::from_log(l) {
+                if let Err(e) = history::record(entry, &self.history_path) {
+                    log::error!("Failed to store upload history: {e}");
+                }
+            }
+        }
+    }
+
+    /// Drops finished logs from the list once there are too many or they are too old.
+    /// They stay in the history.
+    fn prune_logs(&mut self) {
+        let settings = Settings::get();
+        if let Some(max_age) = settings.max_log_age() {
+            let now = SystemTime::now();
+            self.logs.retain(|l| {
+                !l.is_finished()
+                    || l.end_time()
+                        .and_then(|t| now.duration_since(t).ok())
+                        .is_none_or(|age| age <= max_age)
+            });
+        }
+        let mut excess = self.logs.len().saturating_sub(settings.max_logs);
+        // oldest first
+        self.logs.retain(|l| {
+            if excess > 0 && l.is_finished() {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    fn advance_logs(&mut self, handler: &mut impl FnMut(UploaderEvent)) {
+        let offline_dpsreport = self.offline.get(Target::DpsReport);
+        let offline_wingman = self.offline.get(Target::Wingman);
+        let (paused, hold_dpsreport, hold_wingman) = {
+            let settings = Settings::get();
+            let schedule = &settings.schedule;
+            (
+                settings.paused,
+                schedule.holds(Target::DpsReport, self.last_new_log, self.in_combat),
+                schedule.holds(Target::Wingman, self.last_new_log, self.in_combat),
+            )
+        };
+        let (synced, synced_profile) = (&self.synced, self.synced_profile.as_str());
+        // This can easily be extended to support other stuff like discord webhooks
+        for l in self.logs.iter_mut() {
+            if l.is_cancelled() {
+                cancel(l);
+            }
+            if matches!(l.evtc, Step::Pending) {
+                log::trace!("Activating evtc job for {}", l.location.display());
+                l.evtc = Step::Active;
+                if let Err(e) = self.evtc_tx.send((l.id, l.location.clone())) {
+                    log::error!("Failed to send evtc job: {e}");
+                }
+            }
+            // cannot do anything else until the evtc is done
+            if matches!(l.evtc, Step::Active) {
+                log::trace!("we still parsing evtc for {}", l.location.display());
+                continue;
+            }
+            if let Step::Error(e) = &l.evtc {
+                log::error!("Failed to parse evtc for {}: {e}", l.location.display());
+                continue;
+            }
+            // Parsing is cheap and local, only uploads are held back
+            if paused || l.is_awaiting_approval() {
+                l.waiting = false;
+                continue;
+            }
+            l.waiting = (hold_dpsreport && matches!(l.dpsreport, Step::Pending))
+                || (hold_wingman && matches!(l.wingman, Step::Pending));
+            // Both targets use the same profile, the lock is released before calling the handler
+            {
+                let settings = Settings::get();
+                let Step::Done(ref enc) = l.evtc else {
+                    unreachable!()
+                };
+                let (name, profile) = settings.profile_for(enc.pov.as_deref(), enc.boss_id);
+                // The synced uploads belong to the token of one profile
+                if matches!(l.dpsreport, Step::Pending) && name == synced_profile {
+                    if let Some(upload) = find_synced(synced, l) {
+                        log::info!(
+                            "{} was already uploaded: {}",
+                            l.location.display(),
+                            upload.permalink
+                        );
+                        l.dpsreport = Step::Done(upload.clone());
+                        if settings.fetch_ei_json {
+                            l.request_ei_json();
+                        }
+                    }
+                }
+                // An unreachable server keeps its logs pending, the other target carries on
+                if matches!(l.dpsreport, Step::Pending) && !hold_dpsreport && !offline_dpsreport {
+                    let token = profile.dpsreport_token.clone();
+                    if profile.uploads_to(Target::DpsReport, enc.boss_id) {
+                        l.dpsreport = Step::Active;
+                        l.profile = Some(name.to_string());
+                        let options = settings.dpsreport_options.for_boss(enc.boss_id);
+                        l.dpsreport_options = Some(options);
+                        let job = (
+                            l.id,
+                            l.location.clone(),
+                            token,
+                            name.to_string(),
+                            options,
+                            l.cancel.clone(),
+                        );
+                        if let Err(e) = self.dps_tx.send(job) {
+                            log::error!("Failed to send dpsreport job: {e}");
+                        }
+                    } else {
+                        l.dpsreport = Step::Skipped;
+                    }
+                }
+                if matches!(l.wingman, Step::Pending) && !hold_wingman && !offline_wingman {
+                    if profile.uploads_to(Target::Wingman, enc.boss_id) {
+                        l.wingman = Step::Active;
+                        l.profile = Some(name.to_string());
+                        if let Err(e) = self.wingman_tx.send((
+                            l.id,
+                            l.location.clone(),
+                            // Error handling on missing pov (broken log?)
+                            enc.pov.clone().unwrap_or_default(),
+                            enc.boss_id,
+                            l.cancel.clone(),
+                        )) {
+                            log::error!("Failed to send wingman job: {e}");
+                        }
+                    } else {
+                        l.wingman = Step::Skipped;
+                    }
+                }
+            }
+            // Only logs whose details were asked for are pending
+            if let Step::Done(report) = &l.dpsreport {
+                if offline_dpsreport {
+                    // asked for again once dps.report is back
+                } else if matches!(l.ei_json, Step::Pending) && report.encounter.json_available {
+                    l.ei_json = Step::Active;
+                    if let Err(e) = self.ei_tx.send((l.id, report.id.clone())) {
+                        log::error!("Failed to send eijson job: {e}");
+                    }
+                } else if matches!(l.ei_json, Step::Pending) {
+                    let error = anyhow::anyhow!(e("dps.report has no details for this log"));
+                    l.ei_json = Step::Error(error);
+                }
+            }
+            if let Step::Retry(t) = l.dpsreport {
+                if l.dpsreport_count > 3 {
+                    l.dpsreport = Step::Error(anyhow::anyhow!("Retry limit reached"));
+                    handler(UploaderEvent::Failed(Target::DpsReport, l));
+                } else if Instant::now() > t {
+                    l.dpsreport_count += 1;
+                    l.dpsreport = Step::Pending;
+                }
+            }
+        }
+    }
+}
+
+/// dps.report hands out a token for uploads without one. It is stored in the profile the log
+/// was uploaded with, a token the user set is never replaced.
+fn keep_token(settings_path: &Path, profile: &str, token: &str) {
+    {
+        let mut settings = Settings::get_mut();
+        let current = &mut settings.profile_mut(profile).dpsreport_token;
+        if current == token || token.is_empty() {
+            return;
+        }
+        if !current.is_empty() {
+            log::warn!(
+                "dps.report answered with another token than the one of the profile, keeping it"
+            );
+            return;
+        }
+        *current = token.to_string();
+    }
+    // Also persist to disk so user doesn't have to press save in options
+    // might freeze the game on first log upload after install
+    if let Err(e) = Settings::store_token(settings_path, profile, token) {
+        log::error!("Failed to store settings: {e}");
+    }
+}
+
+fn is_upload_of(upload: &DpsReportResponse, log: &Log) -> bool {
+    let (Step::Done(enc), Some(start)) = (&log.evtc, log.start_time()) else {
+        return false;
+    };
+    sync::matches(upload, enc.boss_id, start.timestamp())
+}
+
+/// Name of the profile the log was uploaded with, or would be uploaded with if it wasn't yet
+fn profile_of<'a>(log: &'a Log, settings: &'a Settings) -> Option<&'a str> {
+    if let Some(name) = &log.profile {
+        return Some(name);
+    }
+    let Step::Done(enc) = &log.evtc else {
+        return None;
+    };
+    Some(settings.profile_for(enc.pov.as_deref(), enc.boss_id).0)
+}
+
+/// The synced upload of this log, if it was uploaded before
+fn find_synced<'a>(synced: &'a [DpsReportResponse], log: &Log) -> Option<&'a DpsReportResponse> {
+    synced.iter().find(|u| is_upload_of(u, log))
+}
+
+/// Marks every upload of the log which didn't finish yet as cancelled. The workers drop their
+/// results for it.
+fn cancel(log: &mut Log) {
+    let cancelled = || Step::Error(anyhow::anyhow!(e("Upload cancelled")));
+    if matches!(log.dpsreport, Step::Pending | Step::Active | Step::Retry(_)) {
+        log.dpsreport = cancelled();
+        log.dpsreport_progress = None;
+    }
+    if matches!(log.wingman, Step::Pending | Step::Active | Step::Retry(_)) {
+        log.wingman = cancelled();
+        log.wingman_progress = None;
+    }
+}
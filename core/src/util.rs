@@ -0,0 +1,46 @@
+use std::{
+    fmt::{self, Write},
+    sync::OnceLock,
+};
+
+static TRANSLATOR: OnceLock<fn(&str) -> Option<String>> = OnceLock::new();
+
+/// Lets the front end plug in its translations. Without one everything stays english.
+pub fn set_translator(translate: fn(&str) -> Option<String>) {
+    if TRANSLATOR.set(translate).is_err() {
+        log::warn!("Translator was already set");
+    }
+}
+
+pub fn e(s: &str) -> String {
+    TRANSLATOR
+        .get()
+        .and_then(|translate| translate(s))
+        .unwrap_or_else(|| s.to_string())
+}
+
+/// For translation entries which aren't displayed themselves, like date formats. `key` is never
+/// a valid value so it falls back to `default` if there is no translation.
+pub fn e_or(key: &str, default: &str) -> String {
+    TRANSLATOR
+        .get()
+        .and_then(|translate| translate(key))
+        .filter(|s| s != key)
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Translates a whole sentence and fills its `{}` placeholders in order. Languages put the
+/// numbers in different places, so sentences are never assembled from translated fragments.
+pub fn e_fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let translated = e(template);
+    let mut parts = translated.split("{}");
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for (i, part) in parts.enumerate() {
+        match args.get(i) {
+            Some(arg) => write!(out, "{arg}").expect("writing to a string"),
+            None => out.push_str("{}"),
+        }
+        out.push_str(part);
+    }
+    out
+}
@@ -0,0 +1,111 @@
+use serde::Deserialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, Sender},
+    },
+    thread,
+};
+
+use crate::{
+    common::WorkerMessage,
+    connectivity, http,
+    multipart::{Multipart, Progress},
+    uploader::Target,
+};
+
+/// (log id, path, account name, boss id, cancel flag)
+pub type WingmanJob = (usize, PathBuf, String, u16, Arc<AtomicBool>);
+
+pub const DEFAULT_URL: &str = "https://evtc.bel.st";
+
+#[derive(Debug, Deserialize)]
+struct EvtcResponse {
+    result: bool,
+}
+
+/// `endpoint` is the base url of the wingman instance, see [`DEFAULT_URL`]
+pub fn run(
+    inc: Receiver<WingmanJob>,
+    out: Sender<WorkerMessage>,
+    endpoint: String,
+) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name("wingman-thread".to_string())
+        .spawn(move || {
+            for (id, location, account_name, boss_id, cancel) in inc {
+                // Cancelled while queued, the log already shows it
+                if cancel.load(Ordering::Relaxed) {
+                    continue;
+                }
+                let progress = {
+                    let out = out.clone();
+                    move |p| {
+                        // The main thread only stops listening when unloading
+                        let _ = out.send(WorkerMessage::progress(id, Target::Wingman, p));
+                    }
+                };
+                let result = upload(
+                    &endpoint,
+                    &location,
+                    &account_name,
+                    boss_id,
+                    cancel.clone(),
+                    progress,
+                );
+                if cancel.load(Ordering::Relaxed) {
+                    log::info!("[Wingman] Upload of {} cancelled", location.display());
+                    continue;
+                }
+                let message = match result {
+                    Err(e)
+                        if e.downcast_ref::<ureq::Error>()
+                            .is_some_and(connectivity::is_connection_error) =>
+                    {
+                        let reason = format!("Could not reach {endpoint}: {e}");
+                        log::warn!("[Wingman] {reason}");
+                        WorkerMessage::offline(id, Target::Wingman, reason)
+                    }
+                    result => WorkerMessage::wingman(id, result),
+                };
+                if let Err(e) = out.send(message) {
+                    log::error!("[Wingman] Failed to send wingman result to main thread: {e}");
+                }
+            }
+        })
+        .expect("Could not create wingman thread")
+}
+
+fn upload(
+    endpoint: &str,
+    location: &Path,
+    account_name: &str,
+    boss_id: u16,
+    cancel: Arc<AtomicBool>,
+    progress: impl FnMut(Progress) + Send + 'static,
+) -> anyhow::Result<bool> {
+    log::info!("[Wingman] Uploading {}", location.display());
+
+    let body = Multipart::new()
+        .text("account", account_name)
+        .text("filesize", &std::fs::metadata(location)?.len().to_string())
+        .text("triggerID", &boss_id.to_string())
+        .file("file", location)?;
+    let client = http::client()?;
+    // gw2wingman itself uses /uploadEVTC
+    let resp = client
+        .post(&format!("{endpoint}/evtc"))
+        .set("Content-Type", &body.content_type)
+        .set("Content-Length", &body.len.to_string())
+        .send(body.with_progress(cancel, progress));
+    match resp {
+        // rejected because it's a duplicate
+        // just assume it's ok
+        // ureq reports every status >= 400 as an error
+        Err(ureq::Error::Status(409, _)) => Ok(true),
+        Err(e) => Err(e.into()),
+        Ok(resp) => Ok(resp.into_json().map(|r: EvtcResponse| r.result)?),
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use log_uploader_core::{
+    arcdpslog::{Approval, Log, Step},
+    profile::{NamedProfile, Profile},
+    settings::Settings,
+    summary::Category,
+    uploader::Target,
+};
+
+fn awaiting() -> Log {
+    let mut log = Log::new(PathBuf::from("log.zevtc"));
+    log.approval = Some(Approval {
+        dpsreport: true,
+        wingman: true,
+    });
+    log
+}
+
+#[test]
+fn approve_skips_unpicked_targets() {
+    let mut log = awaiting();
+    log.approval.as_mut().unwrap().toggle(Target::Wingman);
+    log.approve();
+    assert!(!log.is_awaiting_approval());
+    assert!(matches!(log.dpsreport, Step::Pending));
+    assert!(matches!(log.wingman, Step::Skipped));
+}
+
+#[test]
+fn reject_skips_everything() {
+    let mut log = awaiting();
+    log.reject();
+    assert!(!log.is_awaiting_approval());
+    assert!(matches!(log.dpsreport, Step::Skipped));
+    assert!(matches!(log.wingman, Step::Skipped));
+}
+
+#[test]
+fn targets_come_from_the_matching_profile() {
+    let mut settings = Settings::get().clone();
+    let mut guild = NamedProfile::new(
+        "guild".to_string(),
+        Profile {
+            enable_wingman: false,
+            ..Profile::default()
+        },
+    );
+    guild.categories.push(Category::Wing(1));
+    settings.profiles = vec![guild];
+    settings.auto_profile = true;
+    settings.profile.filter_dpsreport.push(21333);
+
+    // Vale Guardian picks the guild profile, not the active one
+    assert_eq!(
+        settings.approval(None, 15438),
+        Approval {
+            dpsreport: true,
+            wingman: false,
+        }
+    );
+    // Freezie stays on the default profile, which filters it from dps.report
+    assert_eq!(
+        settings.approval(None, 21333),
+        Approval {
+            dpsreport: false,
+            wingman: true,
+        }
+    );
+}
+
+#[test]
+fn wvw_logs_cannot_go_to_wingman() {
+    let settings = Settings::get().clone();
+    assert_eq!(
+        settings.approval(None, 1),
+        Approval {
+            dpsreport: true,
+            wingman: false,
+        }
+    );
+}
+
+#[test]
+fn targets_the_profile_skips_cannot_be_picked() {
+    let mut log = awaiting();
+    log.eligible = Approval {
+        dpsreport: true,
+        wingman: false,
+    };
+    log.approval = Some(log.eligible);
+    log.toggle_approval(Target::Wingman);
+    log.toggle_approval(Target::DpsReport);
+    assert_eq!(
+        log.approval,
+        Some(Approval {
+            dpsreport: false,
+            wingman: false,
+        })
+    );
+}
@@ -0,0 +1,340 @@
+//! Shared helpers for the integration tests: a tiny mock http server and synthetic logs.
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// Path including the query string
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn query(&self, key: &str) -> Option<&str> {
+        let (_, query) = self.path.split_once('?')?;
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn body_str(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Waits before sending anything back, like an overloaded server
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// Answers every request with whatever the handler returns and remembers the requests.
+/// The server thread lives until the test process exits.
+pub struct MockServer {
+    port: u16,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    pub fn start(handler: impl Fn(&Request) -> MockResponse + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler = Arc::new(handler);
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                thread::spawn(move || {
+                    if let Err(e) = serve(stream, &*handler, &recorded) {
+                        eprintln!("mock server: {e}");
+                    }
+                });
+            }
+        });
+        Self { port, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+// One request per connection, `Connection: close` keeps clients from reusing it
+fn serve(
+    stream: TcpStream,
+    handler: &(dyn Fn(&Request) -> MockResponse + Send + Sync),
+    recorded: &Mutex<Vec<Request>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = vec![];
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, v)| v.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let request = Request {
+        method,
+        path,
+        headers,
+        body,
+    };
+    let response = handler(&request);
+    recorded.lock().unwrap().push(request);
+
+    thread::sleep(response.delay);
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// A successful dps.report upload response
+pub fn dpsreport_json(permalink: &str, boss_id: u16, success: bool, token: &str) -> String {
+    format!(
+        r#"{{
+            "id": "abcd-20240101-120000_boss",
+            "permalink": "{permalink}",
+            "userToken": "{token}",
+            "encounterTime": 1704106800,
+            "encounter": {{
+                "bossId": {boss_id},
+                "success": {success},
+                "boss": "Vale Guardian",
+                "duration": 60,
+                "isCm": false,
+                "isLegendaryCm": false,
+                "emboldened": 0
+            }},
+            "players": {{}}
+        }}"#
+    )
+}
+
+/// Fresh directory below the system temp dir
+pub fn temp_dir(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!(
+        "log-uploader-test-{name}-{}-{nanos}",
+        std::process::id()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+pub fn write_log(dir: &Path, name: &str, bytes: &[u8]) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+pub const BOSS_ADDR: u64 = 0x1000;
+pub const PLAYER_ADDR: u64 = 0x2000;
+pub const ACCOUNT: &str = "Tester.1234";
+
+// Statechanges, see the arcdps evtc readme
+const CBTS_CHANGEDEAD: u8 = 4;
+const CBTS_LOGSTART: u8 = 9;
+const CBTS_LOGEND: u8 = 10;
+const CBTS_MAXHEALTHUPDATE: u8 = 12;
+const CBTS_POINTOFVIEW: u8 = 13;
+
+/// A minimal revision 1 evtc with one boss and the recording player, zipped like arcdps does
+pub fn zevtc(boss_id: u16, success: bool) -> Vec<u8> {
+    zip("log.evtc", &evtc(boss_id, success))
+}
+
+fn evtc(boss_id: u16, success: bool) -> Vec<u8> {
+    let mut out = vec![];
+    out.extend(b"EVTC20240101");
+    out.push(1); // revision
+    out.extend(boss_id.to_le_bytes());
+    out.push(0);
+
+    out.extend(2u32.to_le_bytes());
+    // npcs have the species id as profession and an elite spec of u32::MAX
+    agent(&mut out, BOSS_ADDR, boss_id as u32, u32::MAX, b"Boss\0");
+    // guardian without elite spec, name is "character\0:account\0subgroup\0"
+    let name = format!("Tester\0:{ACCOUNT}\x001\0");
+    agent(&mut out, PLAYER_ADDR, 1, 0, name.as_bytes());
+
+    out.extend(0u32.to_le_bytes()); // skills
+
+    let start = 1_000;
+    let end = start + 60_000;
+    event(&mut out, start, 0, 0, 1_704_106_800, CBTS_LOGSTART);
+    event(&mut out, start, PLAYER_ADDR, 0, 0, CBTS_POINTOFVIEW);
+    event(
+        &mut out,
+        start,
+        BOSS_ADDR,
+        1_000_000,
+        0,
+        CBTS_MAXHEALTHUPDATE,
+    );
+    if success {
+        event(&mut out, end, BOSS_ADDR, 0, 0, CBTS_CHANGEDEAD);
+    }
+    event(&mut out, end, 0, 0, 1_704_106_860, CBTS_LOGEND);
+    out
+}
+
+fn agent(out: &mut Vec<u8>, addr: u64, prof: u32, elite: u32, name: &[u8]) {
+    out.extend(addr.to_le_bytes());
+    out.extend(prof.to_le_bytes());
+    out.extend(elite.to_le_bytes());
+    // toughness, concentration, healing, hitbox width, condition, hitbox height
+    out.extend([0; 12]);
+    let mut fixed = [0u8; 64];
+    fixed[..name.len()].copy_from_slice(name);
+    out.extend(fixed);
+    out.extend([0; 4]); // padding
+}
+
+fn event(out: &mut Vec<u8>, time: u64, src: u64, dst: u64, value: i32, statechange: u8) {
+    let start = out.len();
+    out.extend(time.to_le_bytes());
+    out.extend(src.to_le_bytes());
+    out.extend(dst.to_le_bytes());
+    out.extend(value.to_le_bytes());
+    // buff_dmg, overstack_value, skillid, src/dst instid, src/dst master instid, iff, buff,
+    // result, is_activation, is_buffremove, is_ninety, is_fifty, is_moving
+    out.extend([0; 28]);
+    out.push(statechange);
+    // is_flanking, is_shields, is_offcycle, padding
+    out.extend([0; 7]);
+    debug_assert_eq!(out.len() - start, 64);
+}
+
+/// Zip archive with a single stored (uncompressed) file
+fn zip(name: &str, data: &[u8]) -> Vec<u8> {
+    let crc = crc32(data);
+    let size = data.len() as u32;
+    let name = name.as_bytes();
+    let mut out = vec![];
+
+    // local file header
+    out.extend(0x0403_4b50u32.to_le_bytes());
+    out.extend(20u16.to_le_bytes()); // version needed
+    out.extend(0u16.to_le_bytes()); // flags
+    out.extend(0u16.to_le_bytes()); // stored
+    out.extend(0u16.to_le_bytes()); // mod time
+    out.extend(0x21u16.to_le_bytes()); // mod date, 1980-01-01
+    out.extend(crc.to_le_bytes());
+    out.extend(size.to_le_bytes());
+    out.extend(size.to_le_bytes());
+    out.extend((name.len() as u16).to_le_bytes());
+    out.extend(0u16.to_le_bytes()); // extra length
+    out.extend(name);
+    out.extend(data);
+
+    let central = out.len() as u32;
+    out.extend(0x0201_4b50u32.to_le_bytes());
+    out.extend(20u16.to_le_bytes()); // version made by
+    out.extend(20u16.to_le_bytes()); // version needed
+    out.extend(0u16.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out.extend(0x21u16.to_le_bytes());
+    out.extend(crc.to_le_bytes());
+    out.extend(size.to_le_bytes());
+    out.extend(size.to_le_bytes());
+    out.extend((name.len() as u16).to_le_bytes());
+    out.extend(0u16.to_le_bytes()); // extra length
+    out.extend(0u16.to_le_bytes()); // comment length
+    out.extend(0u16.to_le_bytes()); // disk number
+    out.extend(0u16.to_le_bytes()); // internal attributes
+    out.extend(0u32.to_le_bytes()); // external attributes
+    out.extend(0u32.to_le_bytes()); // local header offset
+    out.extend(name);
+    let central_size = out.len() as u32 - central;
+
+    // end of central directory
+    out.extend(0x0605_4b50u32.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out.extend(1u16.to_le_bytes());
+    out.extend(1u16.to_le_bytes());
+    out.extend(central_size.to_le_bytes());
+    out.extend(central.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
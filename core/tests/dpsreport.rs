@@ -0,0 +1,428 @@
+mod common;
+
+use std::{
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    time::Duration,
+};
+
+use common::{MockResponse, MockServer};
+use log_uploader_core::{
+    common::WorkerType,
+    dpsreport::{
+        self, CategoryOptions, DpsReportOptions, DpsReportResponse, Generator, Player, Retry,
+        RetryReason, UploadOptions,
+    },
+    multipart::Progress,
+    summary::Category,
+    uploader::Target,
+};
+
+/// Runs a single job through the dps.report worker
+fn upload(
+    endpoint: String,
+    log: &Path,
+    token: &str,
+) -> anyhow::Result<Result<DpsReportResponse, Retry>> {
+    upload_with_progress(endpoint, log, token, UploadOptions::default()).0
+}
+
+/// Also returns the progress reports sent before the result
+fn upload_with_progress(
+    endpoint: String,
+    log: &Path,
+    token: &str,
+    options: UploadOptions,
+) -> (
+    anyhow::Result<Result<DpsReportResponse, Retry>>,
+    Vec<Progress>,
+) {
+    let (job_tx, job_rx) = mpsc::channel();
+    let (tx, rx) = mpsc::channel();
+    let worker = dpsreport::run(job_rx, tx, endpoint);
+    let cancel = Arc::new(AtomicBool::new(false));
+    job_tx
+        .send((
+            7,
+            log.to_path_buf(),
+            token.to_string(),
+            "raids".to_string(),
+            options,
+            cancel,
+        ))
+        .unwrap();
+    drop(job_tx);
+    let mut progress = vec![];
+    loop {
+        let message = rx
+            .recv_timeout(Duration::from_secs(30))
+            .expect("worker to answer");
+        assert_eq!(message.id, 7);
+        match message.payload {
+            WorkerType::Progress(Target::DpsReport, p) => progress.push(p),
+            WorkerType::DpsReport(profile, r) => {
+                // The token dps.report hands out goes to this profile, even if the log is gone
+                assert_eq!(profile, "raids");
+                worker.join().unwrap();
+                return (r, progress);
+            }
+            other => panic!("unexpected payload {other:?}"),
+        }
+    }
+}
+
+fn log_file(name: &str) -> std::path::PathBuf {
+    let dir = common::temp_dir(name);
+    common::write_log(&dir, "log.zevtc", &common::zevtc(15438, true))
+}
+
+fn retry_reason(status: u16, body: &str) -> RetryReason {
+    let server = MockServer::start({
+        let body = body.to_string();
+        move |_| MockResponse::json(status, body.clone())
+    });
+    match upload(server.url(), &log_file("retry"), "") {
+        Ok(Err(retry)) => retry.reason,
+        other => panic!("expected a retry for {status}, got {other:?}"),
+    }
+}
+
+fn error_message(status: u16, body: &str) -> String {
+    let server = MockServer::start({
+        let body = body.to_string();
+        move |_| MockResponse::json(status, body.clone())
+    });
+    match upload(server.url(), &log_file("error"), "") {
+        Err(e) => e.to_string(),
+        other => panic!("expected an error for {status}, got {other:?}"),
+    }
+}
+
+#[test]
+fn success() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            common::dpsreport_json("https://dps.report/abcd", 15438, true, "token"),
+        )
+    });
+    let report = upload(server.url(), &log_file("success"), "token")
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.permalink, "https://dps.report/abcd");
+    assert!(report.encounter.success);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert!(requests[0].path.starts_with("/uploadContent"));
+    assert_eq!(requests[0].query("json"), Some("1"));
+    assert_eq!(requests[0].query("userToken"), Some("token"));
+    assert!(requests[0].body_str().contains(r#"name="file""#));
+}
+
+#[test]
+fn no_token_is_not_sent() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            common::dpsreport_json("https://dps.report/a", 1, true, "new"),
+        )
+    });
+    upload(server.url(), &log_file("no-token"), "")
+        .unwrap()
+        .unwrap();
+    assert_eq!(server.requests()[0].query("userToken"), None);
+}
+
+#[test]
+fn slow_response() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            common::dpsreport_json("https://dps.report/slow", 1, true, ""),
+        )
+        .delayed(Duration::from_secs(2))
+    });
+    let report = upload(server.url(), &log_file("slow"), "")
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.permalink, "https://dps.report/slow");
+}
+
+#[test]
+fn retryable_status_codes() {
+    assert_eq!(retry_reason(408, ""), RetryReason::Timeout);
+    assert_eq!(retry_reason(429, ""), RetryReason::RateLimited);
+    assert_eq!(retry_reason(500, ""), RetryReason::ServerError);
+    assert_eq!(
+        retry_reason(503, "<html>maintenance</html>"),
+        RetryReason::ServerError
+    );
+}
+
+#[test]
+fn forbidden_rate_limited() {
+    let body = r#"{"error": "Too many uploads", "rateLimited": true, "ratePerMinute": 25}"#;
+    assert_eq!(retry_reason(403, body), RetryReason::RateLimited);
+}
+
+#[test]
+fn forbidden_generic_is_retried() {
+    assert_eq!(
+        retry_reason(403, r#"{"error": "Something else"}"#),
+        RetryReason::Forbidden
+    );
+}
+
+#[test]
+fn forbidden_permanent_errors() {
+    for error in [
+        "EI Failure: something broke",
+        "An identical file was uploaded recently",
+        "Encounter is too short for a useful report to be made",
+    ] {
+        let message = error_message(403, &format!(r#"{{"error": "{error}"}}"#));
+        assert!(message.contains("Error 403"), "{message}");
+        assert!(message.contains(error), "{message}");
+    }
+}
+
+#[test]
+fn forbidden_malformed_body() {
+    let message = error_message(403, "not json");
+    assert!(message.contains("Error parsing json"), "{message}");
+}
+
+#[test]
+fn malformed_success_body() {
+    let message = error_message(200, r#"{"permalink": 5}"#);
+    assert!(message.contains("Error parsing json"), "{message}");
+}
+
+#[test]
+fn unknown_status() {
+    let message = error_message(404, "");
+    assert!(message.contains("404"), "{message}");
+}
+
+#[test]
+fn token_is_redacted() {
+    // Nothing listens on port 1, the error contains the request url including the token
+    let (job_tx, job_rx) = mpsc::channel();
+    let (tx, rx) = mpsc::channel();
+    let worker = dpsreport::run(job_rx, tx, "http://127.0.0.1:1".to_string());
+    let cancel = Arc::new(AtomicBool::new(false));
+    job_tx
+        .send((
+            3,
+            log_file("redact"),
+            "secret-token".to_string(),
+            String::new(),
+            UploadOptions::default(),
+            cancel,
+        ))
+        .unwrap();
+    drop(job_tx);
+    worker.join().unwrap();
+    let WorkerType::Offline(Target::DpsReport, reason) = rx.try_recv().unwrap().payload else {
+        panic!("expected the server to be unreachable");
+    };
+    assert!(!reason.contains("secret-token"), "{reason}");
+    assert!(reason.contains("******"), "{reason}");
+}
+
+#[test]
+fn progress_is_reported() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            common::dpsreport_json("https://dps.report/a", 1, true, ""),
+        )
+    });
+    let log = log_file("progress");
+    let (result, progress) = upload_with_progress(server.url(), &log, "", UploadOptions::default());
+    result.unwrap().unwrap();
+
+    let last = progress.last().expect("at least one progress report");
+    assert_eq!(last.sent, last.total);
+    // the body is the file plus the multipart framing
+    assert!(last.total > std::fs::metadata(&log).unwrap().len());
+    assert_eq!(last.total, server.requests()[0].body.len() as u64);
+    assert!(progress.windows(2).all(|w| w[0].sent <= w[1].sent));
+}
+
+#[test]
+fn cancelled_job_is_dropped() {
+    let server = MockServer::start(|_| MockResponse::json(200, "{}"));
+    let (job_tx, job_rx) = mpsc::channel();
+    let (tx, rx) = mpsc::channel();
+    let worker = dpsreport::run(job_rx, tx, server.url());
+    let cancel = Arc::new(AtomicBool::new(false));
+    cancel.store(true, Ordering::Relaxed);
+    job_tx
+        .send((
+            1,
+            log_file("cancel"),
+            String::new(),
+            String::new(),
+            UploadOptions::default(),
+            cancel,
+        ))
+        .unwrap();
+    drop(job_tx);
+    worker.join().unwrap();
+    assert!(rx.try_recv().is_err(), "cancelled job sent a result");
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn unreachable_server_is_offline() {
+    // Nothing listens on the port once the listener is gone
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let (job_tx, job_rx) = mpsc::channel();
+    let (tx, rx) = mpsc::channel();
+    let worker = dpsreport::run(job_rx, tx, format!("http://127.0.0.1:{port}"));
+    let cancel = Arc::new(AtomicBool::new(false));
+    job_tx
+        .send((
+            2,
+            log_file("offline"),
+            String::new(),
+            String::new(),
+            UploadOptions::default(),
+            cancel,
+        ))
+        .unwrap();
+    drop(job_tx);
+    worker.join().unwrap();
+    let message = rx.try_recv().expect("worker to answer");
+    assert!(matches!(
+        message.payload,
+        WorkerType::Offline(Target::DpsReport, _)
+    ));
+}
+
+#[test]
+fn upload_options_are_sent() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            common::dpsreport_json("https://dps.report/a", 1, true, ""),
+        )
+    });
+    let options = UploadOptions {
+        anonymous: true,
+        detailed_wvw: true,
+        generator: Generator::EliteInsights,
+    };
+    let (result, _) = upload_with_progress(server.url(), &log_file("options"), "", options);
+    result.unwrap().unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].query("generator"), Some("ei"));
+    assert_eq!(requests[0].query("anonymous"), Some("true"));
+    assert_eq!(requests[0].query("detailedwvw"), Some("true"));
+}
+
+#[test]
+fn category_overrides_apply() {
+    let wvw = UploadOptions {
+        detailed_wvw: true,
+        ..UploadOptions::default()
+    };
+    let options = DpsReportOptions {
+        default: UploadOptions::default(),
+        overrides: vec![CategoryOptions {
+            category: Category::WvW,
+            options: wvw,
+        }],
+    };
+    assert_eq!(options.for_boss(1), wvw);
+    // Vale Guardian
+    assert_eq!(options.for_boss(15438), UploadOptions::default());
+}
+
+#[test]
+fn full_response_is_parsed() {
+    let json = r#"{
+        "id": "abcd-20240101-120000_vg",
+        "permalink": "https://dps.report/abcd-20240101-120000_vg",
+        "uploadTime": 1704106900,
+        "encounterTime": 1704106800,
+        "generator": "Elite Insights",
+        "generatorId": 1,
+        "generatorVersion": 2,
+        "language": "en",
+        "languageId": 0,
+        "evtc": {"type": "EVTC", "version": "EVTC20240612", "bossId": 15438},
+        "players": {
+            "Tester.1234": {
+                "display_name": "Tester.1234",
+                "character_name": "Test Character",
+                "profession": 1,
+                "elite_spec": 18
+            },
+            "Other.5678": {
+                "displayName": "Other.5678",
+                "characterName": "Other Character",
+                "profession": 2,
+                "eliteSpec": 0
+            }
+        },
+        "encounter": {
+            "uniqueId": "12345",
+            "success": true,
+            "duration": 95,
+            "compDps": 123456,
+            "numberOfPlayers": 2,
+            "numberOfGroups": 1,
+            "bossId": 15438,
+            "boss": "Vale Guardian",
+            "isCm": false,
+            "isLegendaryCm": false,
+            "emboldened": 0,
+            "gw2Build": 164824,
+            "jsonAvailable": true
+        },
+        "report": {"anonymous": false, "detailed": false},
+        "userToken": "token"
+    }"#;
+    let report: DpsReportResponse = serde_json::from_str(json).unwrap();
+    assert_eq!(report.generator, "Elite Insights");
+    assert_eq!(report.evtc.version, "EVTC20240612");
+    assert_eq!(report.encounter.comp_dps, 123456);
+    assert_eq!(report.encounter.duration, 95);
+    assert!(report.encounter.json_available);
+    assert!(report.uploaded_at().is_some());
+    let players = report.players.to_vec();
+    let names: Vec<_> = players.iter().map(|p| p.character_name.as_str()).collect();
+    assert_eq!(names, ["Other Character", "Test Character"]);
+    assert_eq!(players[1].specialization(), "Berserker");
+    // Core builds fall back to the profession
+    assert_eq!(players[0].specialization(), "Warrior");
+}
+
+#[test]
+fn newer_elite_specs_are_known() {
+    let player = |profession: u32, elite_spec: u32| -> Player {
+        serde_json::from_value(serde_json::json!({
+            "display_name": "Tester.1234",
+            "character_name": "Test Character",
+            "profession": profession,
+            "elite_spec": elite_spec,
+        }))
+        .unwrap()
+    };
+    assert_eq!(player(1, 81).specialization(), "Luminary");
+    assert_eq!(player(2, 74).specialization(), "Paragon");
+    assert_eq!(player(5, 77).specialization(), "Antiquary");
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::{
+    fs::{self, File},
+    time::{Duration, SystemTime},
+};
+
+use common::{MockResponse, MockServer};
+use log_uploader_core::eijson;
+
+const JSON: &str = r#"{
+    "fightName": "Vale Guardian",
+    "durationMS": 95000,
+    "success": true,
+    "players": [
+        {
+            "name": "Slow", "account": "Slow.1234", "profession": "Guardian", "group": 1,
+            "dpsTargets": [[{"dps": 1000, "damage": 95000}]],
+            "squadBuffs": [{"id": 1187, "buffData": [{"generation": 45.5}]}]
+        },
+        {
+            "name": "Fast", "account": "Fast.1234", "profession": "Virtuoso", "group": 1,
+            "dpsTargets": [[{"dps": 30000, "damage": 2850000}], [{"dps": 500, "damage": 1000}]]
+        },
+        {
+            "name": "Helper", "account": "", "profession": "Sword", "group": 1,
+            "notInSquad": true,
+            "dpsTargets": [[{"dps": 99999, "damage": 1}]]
+        }
+    ],
+    "mechanics": [
+        {"name": "Green", "description": "Stood in the green", "mechanicsData": [
+            {"time": 1000, "actor": "Slow"}, {"time": 2000, "actor": "Slow"}
+        ]},
+        {"name": "Breakbar", "description": "Broke the bar", "mechanicsData": [
+            {"time": 3000, "actor": "Vale Guardian"}
+        ]},
+        {"name": "Got up", "description": "Got up", "mechanicsData": [
+            {"time": 4000, "actor": "Slow"}
+        ]},
+        {"name": "Eligible", "description": "Still eligible", "isAchievementEligibility": true,
+            "mechanicsData": [{"time": 5000, "actor": "Fast"}]
+        }
+    ],
+    "targets": [{"name": "Vale Guardian"}]
+}"#;
+
+#[test]
+fn json_is_fetched_once_and_summarized() {
+    let server = MockServer::start(|_| MockResponse::json(200, JSON));
+    let cache = common::temp_dir("eijson");
+
+    let json = eijson::fetch(&server.url(), &cache, "abcd-20240101-120000_vg").unwrap();
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].path.starts_with("/getJson"));
+    assert_eq!(requests[0].query("id"), Some("abcd-20240101-120000_vg"));
+
+    let ranking: Vec<_> = json.dps_ranking().iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(ranking, ["Fast", "Slow"]);
+    assert_eq!(json.dps_ranking()[0].target_dps(), 30500);
+    assert_eq!(json.players[0].boon_generation(1187), 45.5);
+    assert_eq!(json.players[0].boon_generation(740), 0.0);
+
+    let failures = json.failures_per_player();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures["Slow"].len(), 1);
+    assert_eq!(failures["Slow"]["Green"], 2);
+
+    // The second time comes from the cache
+    let cached = eijson::fetch(&server.url(), &cache, "abcd-20240101-120000_vg").unwrap();
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(cached.fight_name, "Vale Guardian");
+    assert_eq!(cached.dps_ranking()[0].target_dps(), 30500);
+}
+
+#[test]
+fn old_cache_entries_are_pruned() {
+    let cache = common::temp_dir("eijson-prune");
+    let old = common::write_log(&cache, "old.json", b"{}");
+    let fresh = common::write_log(&cache, "fresh.json", b"{}");
+    let two_days = Duration::from_secs(2 * 24 * 60 * 60);
+    File::options()
+        .write(true)
+        .open(&old)
+        .unwrap()
+        .set_modified(SystemTime::now() - two_days)
+        .unwrap();
+
+    eijson::prune_cache(&cache, two_days / 2);
+    assert!(!old.exists());
+    assert!(fresh.exists());
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 1);
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use std::fs;
+
+use log_uploader_core::{dpsreport::DpsReportResponse, history};
+
+fn upload(permalink: &str) -> history::Entry {
+    let upload: DpsReportResponse =
+        serde_json::from_str(&common::dpsreport_json(permalink, 15438, true, "")).unwrap();
+    history::Entry::from_upload(&upload, None)
+}
+
+#[test]
+fn known_uploads_are_recorded_once() {
+    let path = common::temp_dir("history").join("history.jsonl");
+    assert!(history::record(upload("https://dps.report/a"), &path).unwrap());
+
+    let added = history::record_all(
+        [
+            upload("https://dps.report/a"),
+            upload("https://dps.report/b"),
+            upload("https://dps.report/b"),
+        ],
+        &path,
+    )
+    .unwrap();
+    assert_eq!(added, 1);
+    assert_eq!(history::get().len(), 2);
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+    // Named like the local logs, not like dps.report
+    assert_eq!(history::get()[1].boss, history::get()[0].boss);
+}
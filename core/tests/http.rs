@@ -0,0 +1,72 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{MockResponse, MockServer};
+use log_uploader_core::{
+    http::{self, Http, ProxyMode, USER_AGENT},
+    settings::Settings,
+};
+
+#[test]
+fn user_agent_is_sent() {
+    let server = MockServer::start(|_| MockResponse::json(200, "{}"));
+    http::build(&Http::default())
+        .unwrap()
+        .get(&server.url())
+        .call()
+        .unwrap();
+    let requests = server.requests();
+    let agent = requests[0]
+        .headers
+        .iter()
+        .find(|(name, _)| name == "user-agent")
+        .map(|(_, value)| value.as_str());
+    assert_eq!(agent, Some(USER_AGENT));
+    assert!(USER_AGENT.ends_with(env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn stalled_response_times_out() {
+    let server =
+        MockServer::start(|_| MockResponse::json(200, "{}").delayed(Duration::from_secs(5)));
+    let agent = http::build(&Http {
+        read_timeout: 1,
+        ..Http::default()
+    })
+    .unwrap();
+    let err = agent
+        .get(&server.url())
+        .call()
+        .expect_err("read to time out");
+    assert!(matches!(err, ureq::Error::Transport(_)), "{err}");
+}
+
+#[test]
+fn invalid_settings_are_reported() {
+    let bad_proxy = Http {
+        proxy: ProxyMode::Manual,
+        proxy_url: "ftp://nope".to_string(),
+        ..Http::default()
+    };
+    assert!(http::build(&bad_proxy).is_err());
+
+    let missing_ca = Http {
+        ca_file: "/does/not/exist.pem".to_string(),
+        ..Http::default()
+    };
+    let err = http::build(&missing_ca).unwrap_err();
+    assert!(format!("{err:#}").contains("exist.pem"), "{err:#}");
+}
+
+#[test]
+fn client_follows_settings() {
+    // The only test touching the global settings
+    let server = MockServer::start(|_| MockResponse::json(200, "{}"));
+    Settings::get_mut().http.proxy = ProxyMode::None;
+    http::client().unwrap().get(&server.url()).call().unwrap();
+
+    Settings::get_mut().http.proxy = ProxyMode::Manual;
+    Settings::get_mut().http.proxy_url = "ftp://nope".to_string();
+    assert!(http::client().is_err());
+}
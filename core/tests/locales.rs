@@ -0,0 +1,78 @@
+//! Every text passed to the translation functions needs an entry in each locale, otherwise it
+//! silently shows up in english.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const FUNCTIONS: [&str; 3] = ["e(", "e_or(", "e_fill("];
+
+fn sources(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            sources(&path, files);
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+/// String literals passed as the first argument, calls with anything else are skipped
+fn keys(source: &str) -> Vec<String> {
+    let mut keys = vec![];
+    for function in FUNCTIONS {
+        for (i, _) in source.match_indices(function) {
+            // `name(`, `.e(` and the like
+            let before = source[..i].chars().next_back();
+            if before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.') {
+                continue;
+            }
+            let rest = source[i + function.len()..].trim_start();
+            let Some(rest) = rest.strip_prefix('"') else {
+                continue;
+            };
+            let mut key = String::new();
+            let mut chars = rest.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('n') => key.push('\n'),
+                        Some(c) => key.push(c),
+                        None => break,
+                    },
+                    c => key.push(c),
+                }
+            }
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+#[test]
+fn every_text_is_translated() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files = vec![];
+    sources(&root.join("src"), &mut files);
+    sources(&root.join("../src"), &mut files);
+    let mut used: Vec<_> = files
+        .iter()
+        .flat_map(|f| keys(&fs::read_to_string(f).unwrap()))
+        .collect();
+    used.sort();
+    used.dedup();
+    // Guards against the scan silently finding nothing
+    assert!(used.iter().any(|k| k == "Uploads"));
+    assert!(used.iter().any(|k| k == "{} min ago"));
+
+    for lang in ["de", "fr", "es"] {
+        let json = fs::read_to_string(root.join(format!("../locales/{lang}.json"))).unwrap();
+        let locale: HashMap<String, String> = serde_json::from_str(&json).unwrap();
+        let missing: Vec<_> = used.iter().filter(|k| !locale.contains_key(*k)).collect();
+        assert!(missing.is_empty(), "{lang}.json is missing {missing:#?}");
+    }
+}
@@ -0,0 +1,55 @@
+//! An unreachable server only holds back its own uploads. Settings are global, so everything runs
+//! in a single test.
+mod common;
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{MockResponse, MockServer};
+use log_uploader_core::{arcdpslog::Step, settings::Settings, uploader::Uploader};
+
+#[test]
+fn unreachable_dpsreport_does_not_hold_back_wingman() {
+    let dir = common::temp_dir("offline");
+    // Nothing listens on the port once the listener is gone
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let wingman = MockServer::start(|_| MockResponse::json(200, r#"{"result": true}"#));
+    {
+        let mut settings = Settings::get_mut();
+        settings.endpoints.dpsreport = Some(format!("http://127.0.0.1:{port}"));
+        settings.endpoints.wingman = Some(wingman.url());
+    }
+
+    let mut uploader = Uploader::new(
+        None,
+        false,
+        dir.join("settings.json"),
+        dir.join("history.jsonl"),
+    );
+    uploader.enqueue(common::write_log(
+        &dir,
+        "vg.zevtc",
+        &common::zevtc(15438, true),
+    ));
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while !matches!(uploader.logs[0].wingman, Step::Done(_)) || !uploader.is_offline() {
+        assert!(Instant::now() < deadline, "timed out");
+        uploader.tick(|_| {});
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    let log = &uploader.logs[0];
+    assert!(matches!(log.wingman, Step::Done(true)));
+    assert_eq!(wingman.requests().len(), 1);
+    // Waits for the connection without using up its retries
+    assert!(matches!(log.dpsreport, Step::Pending));
+    assert_eq!(log.dpsreport_count, 0);
+
+    uploader.shutdown();
+}
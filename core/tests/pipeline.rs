@@ -0,0 +1,138 @@
+//! Drives the whole watcher -> evtc -> upload pipeline. Settings are global, so everything runs in
+//! a single test.
+mod common;
+
+use std::{
+    fs, thread,
+    time::{Duration, Instant},
+};
+
+use common::{MockResponse, MockServer};
+use log_uploader_core::{
+    arcdpslog::Step,
+    profile::{NamedProfile, Profile},
+    settings::Settings,
+    uploader::{Uploader, UploaderEvent},
+};
+
+fn tick_until(uploader: &mut Uploader, events: &mut Vec<String>, done: impl Fn(&Uploader) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while !done(uploader) {
+        assert!(
+            Instant::now() < deadline,
+            "timed out, events so far: {events:?}"
+        );
+        uploader.tick(|event| {
+            events.push(match event {
+                UploaderEvent::Parsed(_) => "parsed".to_string(),
+                UploaderEvent::Uploaded(target, _) => format!("uploaded {}", target.name()),
+                UploaderEvent::Failed(target, _) => format!("failed {}", target.name()),
+                UploaderEvent::RateLimited => "rate limited".to_string(),
+                UploaderEvent::WatcherLost(path) => format!("lost {path}"),
+                UploaderEvent::Offline => "offline".to_string(),
+                UploaderEvent::Online => "online".to_string(),
+            })
+        });
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn watched_log_is_parsed_and_uploaded() {
+    let dir = common::temp_dir("pipeline");
+    let logs = dir.join("arcdps.cbtlogs");
+    fs::create_dir_all(&logs).unwrap();
+    let settings_path = dir.join("settings.json");
+    let history_path = dir.join("history.jsonl");
+
+    let dpsreport = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            common::dpsreport_json("https://dps.report/vg", 15438, true, "new-token"),
+        )
+    });
+    let wingman = MockServer::start(|_| MockResponse::json(200, r#"{"result": true}"#));
+    {
+        let mut settings = Settings::get_mut();
+        settings.logpath = logs.display().to_string();
+        settings.profile.dpsreport_token = "default-token".to_string();
+        // Picked for logs of the test account, dps.report hands it a token on the first upload
+        let mut own = NamedProfile::new("own".to_string(), Profile::default());
+        own.accounts.push(common::ACCOUNT.to_string());
+        settings.profiles.push(own);
+        settings.auto_profile = true;
+        settings.endpoints.dpsreport = Some(dpsreport.url());
+        settings.endpoints.wingman = Some(wingman.url());
+        settings.store(&settings_path).unwrap();
+        // Like the overrides of the CLI, only in memory
+        settings.session_gap_minutes = 5;
+    }
+
+    let mut uploader = Uploader::new(
+        Some(&logs),
+        false,
+        settings_path.clone(),
+        history_path.clone(),
+    );
+    let mut events = vec![];
+
+    // arcdps writes the log under a temporary name and renames it once it's compressed
+    let tmp = common::write_log(&logs, "20240101-120000.tmp", &common::zevtc(15438, true));
+    fs::rename(&tmp, logs.join("20240101-120000.zevtc")).unwrap();
+    tick_until(&mut uploader, &mut events, |u| {
+        u.logs.len() == 1 && u.logs[0].is_finished()
+    });
+
+    let log = &uploader.logs[0];
+    let Step::Done(enc) = &log.evtc else {
+        panic!("evtc not parsed: {:?}", log.evtc);
+    };
+    assert_eq!(enc.boss_id, 15438);
+    assert!(
+        enc.pov
+            .as_deref()
+            .is_some_and(|pov| pov.contains(common::ACCOUNT))
+    );
+    assert_eq!(log.success(), Some(true));
+    let Step::Done(report) = &log.dpsreport else {
+        panic!("dps.report upload failed: {:?}", log.dpsreport);
+    };
+    assert_eq!(report.permalink, "https://dps.report/vg");
+    assert!(matches!(log.wingman, Step::Done(true)));
+    // The uploads run in parallel, so only the first event has a fixed position
+    assert_eq!(events[0], "parsed");
+    events.sort();
+    assert_eq!(
+        events,
+        ["parsed", "uploaded Wingman", "uploaded dps.report"]
+    );
+
+    assert_eq!(dpsreport.requests()[0].query("userToken"), None);
+    assert_eq!(log.profile.as_deref(), Some("own"));
+    assert!(wingman.requests()[0].body_str().contains(common::ACCOUNT));
+
+    // The token handed out by dps.report is kept and persisted, a token set by the user is not
+    // replaced
+    assert_eq!(Settings::get().profile("own").dpsreport_token, "new-token");
+    assert_eq!(Settings::get().profile.dpsreport_token, "default-token");
+    let stored: Settings =
+        serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+    assert_eq!(stored.profile("own").dpsreport_token, "new-token");
+    assert_eq!(stored.session_gap_minutes, 30);
+    assert!(
+        fs::read_to_string(&history_path)
+            .unwrap()
+            .contains("https://dps.report/vg")
+    );
+
+    // Broken logs stop after parsing and never reach the servers
+    let broken = common::write_log(&dir, "broken.zevtc", b"not a zip");
+    uploader.enqueue(broken);
+    tick_until(&mut uploader, &mut events, |u| {
+        u.logs.len() == 2 && matches!(u.logs[1].evtc, Step::Error(_))
+    });
+    assert_eq!(dpsreport.requests().len(), 1);
+    assert_eq!(wingman.requests().len(), 1);
+
+    uploader.shutdown();
+}
@@ -0,0 +1,56 @@
+use log_uploader_core::{
+    profile::{NamedProfile, Profile},
+    settings::Settings,
+    summary::Category,
+};
+
+fn named(name: &str, token: &str) -> NamedProfile {
+    NamedProfile::new(
+        name.to_string(),
+        Profile {
+            dpsreport_token: token.to_string(),
+            ..Profile::default()
+        },
+    )
+}
+
+#[test]
+fn accounts_and_categories_match() {
+    let mut p = named("guild", "");
+    p.accounts.push("Tester.1234".to_string());
+    p.categories.push(Category::Fractal);
+    assert!(p.matches(Some(":tester.1234"), 15438));
+    // MAMA
+    assert!(p.matches(None, 17021));
+    assert!(!p.matches(Some(":Other.5678"), 15438));
+}
+
+#[test]
+fn automatic_selection_falls_back_to_active() {
+    let mut settings = Settings::get().clone();
+    settings.profile.dpsreport_token = "default-token".to_string();
+    let mut guild = named("guild", "guild-token");
+    guild.categories.push(Category::Wing(1));
+    settings.profiles = vec![guild, named("training", "training-token")];
+    settings.active_profile = "training".to_string();
+
+    let (name, profile) = settings.profile_for(None, 15438);
+    assert_eq!(
+        (name, profile.dpsreport_token.as_str()),
+        ("training", "training-token")
+    );
+
+    settings.auto_profile = true;
+    let (name, profile) = settings.profile_for(None, 15438);
+    assert_eq!(
+        (name, profile.dpsreport_token.as_str()),
+        ("guild", "guild-token")
+    );
+    // Freezie
+    let (name, _) = settings.profile_for(None, 21333);
+    assert_eq!(name, "training");
+
+    // A deleted profile falls back to the default one
+    settings.active_profile = "gone".to_string();
+    assert_eq!(settings.active().dpsreport_token, "default-token");
+}
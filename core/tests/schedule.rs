@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+use log_uploader_core::{
+    schedule::{Defer, Schedule},
+    uploader::Target,
+};
+
+fn schedule(defer: Defer) -> Schedule {
+    Schedule {
+        defer,
+        idle_secs: 60,
+        dpsreport: true,
+        wingman: false,
+    }
+}
+
+#[test]
+fn never_holds() {
+    let s = schedule(Defer::Never);
+    assert!(!s.holds(Target::DpsReport, Some(Instant::now()), true));
+}
+
+#[test]
+fn idle_waits_for_the_last_log() {
+    let s = schedule(Defer::Idle);
+    assert!(s.holds(Target::DpsReport, Some(Instant::now()), false));
+    let old = Instant::now() - Duration::from_secs(61);
+    assert!(!s.holds(Target::DpsReport, Some(old), false));
+    assert!(!s.holds(Target::DpsReport, None, false));
+}
+
+#[test]
+fn out_of_combat_follows_combat() {
+    let s = schedule(Defer::OutOfCombat);
+    assert!(s.holds(Target::DpsReport, None, true));
+    assert!(!s.holds(Target::DpsReport, Some(Instant::now()), false));
+}
+
+#[test]
+fn only_opted_in_targets_wait() {
+    let s = schedule(Defer::OutOfCombat);
+    assert!(!s.holds(Target::Wingman, None, true));
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use std::path::PathBuf;
+
+use log_uploader_core::{
+    arcdpslog::{Log, Step},
+    dpsreport::DpsReportResponse,
+    evtc::EncounterSummary,
+    settings::Settings,
+    summary,
+};
+
+fn uploaded(permalink: &str, boss_id: u16, success: bool, encounter_time: i64) -> Log {
+    let mut report: DpsReportResponse =
+        serde_json::from_str(&common::dpsreport_json(permalink, boss_id, success, "")).unwrap();
+    report.encounter_time = encounter_time;
+    let mut log = Log::new(PathBuf::from(format!("{encounter_time}.zevtc")));
+    log.evtc = Step::Done(EncounterSummary {
+        boss_id,
+        pov: None,
+        players: vec![],
+    });
+    log.dpsreport = Step::Done(report);
+    log
+}
+
+fn settings() -> Settings {
+    let mut settings = Settings::get().clone();
+    settings.copy_summary = true;
+    settings.profile.dpsreport_copyformat = "@1".to_string();
+    settings
+}
+
+#[test]
+fn latest_kill_is_listed_whatever_the_log_order() {
+    let start = 1_704_106_800;
+    // Newest first, like the log list with the reversed order
+    let logs = [
+        uploaded("https://dps.report/second", 15438, true, start + 600),
+        uploaded("https://dps.report/first", 15438, true, start),
+    ];
+    let text = summary::collect_summary(&logs, &settings());
+    assert!(text.contains("https://dps.report/second"));
+    assert!(!text.contains("https://dps.report/first"));
+}
+
+#[test]
+fn clear_time_covers_the_listed_bosses_only() {
+    let start = 1_704_106_800;
+    let logs = [
+        // Vale Guardian, killed in a minute
+        uploaded("https://dps.report/vg", 15438, true, start),
+        // Gorseval, a wipe half an hour later that isn't copied
+        uploaded("https://dps.report/gorse", 15429, false, start + 1800),
+    ];
+    let mut settings = settings();
+    settings.copy_failure = false;
+    let text = summary::collect_summary(&logs, &settings);
+    assert!(!text.contains("https://dps.report/gorse"));
+    assert!(text.contains("1/2 kills, 1m 00s"), "{text}");
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{MockResponse, MockServer};
+use log_uploader_core::{
+    settings::Settings,
+    sync::{self, SyncState},
+    uploader::Uploader,
+};
+
+#[test]
+fn pages_through_all_uploads() {
+    let server = MockServer::start(|req| {
+        let page = req.query("page").unwrap_or("1");
+        let upload =
+            common::dpsreport_json(&format!("https://dps.report/page{page}"), 15438, true, "");
+        MockResponse::json(200, format!(r#"{{"pages": 2, "uploads": [{upload}]}}"#))
+    });
+    let uploads = sync::fetch_uploads(&server.url(), "token").unwrap();
+    let permalinks: Vec<_> = uploads.iter().map(|u| u.permalink.as_str()).collect();
+    assert_eq!(
+        permalinks,
+        ["https://dps.report/page1", "https://dps.report/page2"]
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].path.starts_with("/getUploads"));
+    assert_eq!(requests[0].query("userToken"), Some("token"));
+}
+
+#[test]
+fn needs_a_token() {
+    assert!(sync::fetch_uploads("http://127.0.0.1:1", "").is_err());
+}
+
+#[test]
+fn matches_by_boss_and_start() {
+    let upload = serde_json::from_str(&common::dpsreport_json("a", 15438, true, "")).unwrap();
+    assert!(sync::matches(&upload, 15438, 1704106800 + 2));
+    assert!(!sync::matches(&upload, 15438, 1704106800 + 60));
+    assert!(!sync::matches(&upload, 15429, 1704106800));
+}
+
+#[test]
+fn sync_starts_while_the_settings_are_locked() {
+    let dir = common::temp_dir("sync-locked");
+    let server = MockServer::start(|_| {
+        let upload = common::dpsreport_json("https://dps.report/locked", 15438, true, "");
+        MockResponse::json(200, format!(r#"{{"pages": 1, "uploads": [{upload}]}}"#))
+    });
+    {
+        let mut settings = Settings::get_mut();
+        settings.endpoints.dpsreport = Some(server.url());
+        settings.profile.dpsreport_token = "token".to_string();
+    }
+    let mut uploader = Uploader::new(
+        None,
+        false,
+        dir.join("settings.json"),
+        dir.join("history.jsonl"),
+    );
+
+    // The addon keeps the settings locked for the whole frame, a second lock would never return
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let settings = Settings::get_mut();
+        uploader.sync_history(&settings.active_profile, &settings.active().dpsreport_token);
+        drop(settings);
+        tx.send(uploader).unwrap();
+    });
+    let mut uploader = rx
+        .recv_timeout(Duration::from_secs(10))
+        .expect("sync_history not to lock the settings");
+
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while uploader.sync == SyncState::Running {
+        assert!(Instant::now() < deadline, "sync timed out");
+        uploader.tick(|_| {});
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert_eq!(
+        uploader.sync,
+        SyncState::Done {
+            uploads: 1,
+            imported: 1
+        }
+    );
+    assert_eq!(server.requests()[0].query("userToken"), Some("token"));
+    uploader.shutdown();
+}
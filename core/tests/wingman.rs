@@ -0,0 +1,93 @@
+mod common;
+
+use std::{
+    path::Path,
+    sync::{Arc, atomic::AtomicBool, mpsc},
+    time::Duration,
+};
+
+use common::{MockResponse, MockServer};
+use log_uploader_core::{common::WorkerType, wingman};
+
+/// Runs a single job through the wingman worker
+fn upload(endpoint: String, log: &Path) -> anyhow::Result<bool> {
+    let (job_tx, job_rx) = mpsc::channel();
+    let (tx, rx) = mpsc::channel();
+    let worker = wingman::run(job_rx, tx, endpoint);
+    let cancel = Arc::new(AtomicBool::new(false));
+    job_tx
+        .send((
+            3,
+            log.to_path_buf(),
+            common::ACCOUNT.to_string(),
+            15438,
+            cancel,
+        ))
+        .unwrap();
+    drop(job_tx);
+    loop {
+        let message = rx
+            .recv_timeout(Duration::from_secs(30))
+            .expect("worker to answer");
+        assert_eq!(message.id, 3);
+        match message.payload {
+            WorkerType::Progress(..) => continue,
+            WorkerType::Wingman(r) => {
+                worker.join().unwrap();
+                return r;
+            }
+            other => panic!("unexpected payload {other:?}"),
+        }
+    }
+}
+
+fn respond(status: u16, body: &'static str) -> (MockServer, anyhow::Result<bool>) {
+    let server = MockServer::start(move |_| MockResponse::json(status, body));
+    let dir = common::temp_dir("wingman");
+    let log = common::write_log(&dir, "log.zevtc", &common::zevtc(15438, true));
+    let result = upload(server.url(), &log);
+    (server, result)
+}
+
+#[test]
+fn success() {
+    let (server, result) = respond(200, r#"{"result": true}"#);
+    assert!(result.unwrap());
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/evtc");
+    let body = requests[0].body_str();
+    for field in ["account", "filesize", "triggerID", "file"] {
+        assert!(
+            body.contains(&format!(r#"name="{field}""#)),
+            "{field} missing"
+        );
+    }
+    assert!(body.contains(common::ACCOUNT));
+    assert!(body.contains("15438"));
+}
+
+#[test]
+fn rejected() {
+    let (_, result) = respond(200, r#"{"result": false}"#);
+    assert!(!result.unwrap());
+}
+
+#[test]
+fn duplicate_counts_as_success() {
+    let (_, result) = respond(409, "");
+    assert!(result.unwrap());
+}
+
+#[test]
+fn server_error() {
+    let (_, result) = respond(500, "");
+    assert!(result.is_err());
+}
+
+#[test]
+fn malformed_body() {
+    let (_, result) = respond(200, "<html>");
+    assert!(result.is_err());
+}
@@ -1,59 +1,30 @@
-use anyhow::{Error, Result};
-use chrono::DateTime;
-use chrono::Local;
+use log_uploader_core::arcdpslog::{Log, Step, format_time};
+use log_uploader_core::dpsreport::DpsReportResponse;
+use log_uploader_core::evtc::{EncounterSummary, Outcome, icon_identifier};
+use log_uploader_core::settings::Settings;
+use log_uploader_core::summary;
+use log_uploader_core::table::Column;
+use log_uploader_core::theme::Theme;
+use log_uploader_core::uploader::Target;
 use nexus::imgui::Image;
 use nexus::imgui::ImageButton;
 use nexus::imgui::MouseButton;
+use nexus::imgui::ProgressBar;
+use nexus::imgui::TextureId;
 use nexus::imgui::Ui;
 use nexus::texture::get_texture;
-use revtc::{bossdata::BossId, evtc::Encounter};
+use revtc::bossdata::BossId;
 use std::cell::Cell;
 use std::time::Instant;
-use std::{path::PathBuf, time::SystemTime};
 
 use crate::assets::DPSREPORT;
 use crate::assets::OPEN_IN_FOLDER;
 use crate::assets::WINGMAN;
-use crate::common::GREEN;
-use crate::common::RED;
-use crate::dpsreport::DpsReportResponse;
-use crate::evtc::identifier_from_agent;
+use crate::theme::ThemeExt;
 use crate::util;
 use crate::util::UiExt;
 use crate::util::e;
-
-// Maybe this needs a retry option for retryable errors
-#[derive(Debug)]
-pub enum Step<T> {
-    Pending,
-    Active,
-    Done(T),
-    Skipped,
-    Error(Error),
-    Retry(Instant),
-}
-
-impl<T> Step<T> {
-    pub fn from_value(value: Result<T>) -> Self {
-        match value {
-            Ok(v) => Self::Done(v),
-            Err(e) => Self::Error(e),
-        }
-    }
-}
-
-pub struct Log {
-    pub location: PathBuf,
-    pub evtc: Step<Encounter>,
-    pub dpsreport: Step<DpsReportResponse>,
-    pub dpsreport_count: u32,
-    pub wingman: Step<bool>,
-}
-
-fn format_time(time: SystemTime) -> String {
-    let dt = DateTime::<Local>::from(time);
-    format!("{}", dt.format("%R"))
-}
+use crate::util::e_fill;
 
 const PULSE_SPEED: f32 = 5.0;
 fn pulse(t: f32) -> f32 {
@@ -61,257 +32,496 @@ fn pulse(t: f32) -> f32 {
     (1.0 + t.sin()) * 0.5
 }
 
-impl Log {
-    pub fn new(location: PathBuf) -> Self {
-        use Step as S;
-        Self {
-            location,
-            evtc: S::Pending,
-            dpsreport: S::Pending,
-            dpsreport_count: 0,
-            wingman: S::Pending,
-        }
-    }
-
-    fn basename(&self) -> String {
-        self.location
-            .parent()
-            .and_then(|p| p.file_name())
-            .unwrap_or_default()
-            .to_string_lossy()
-            .into_owned()
-    }
+/// What the user did with a row, applied once the table is done rendering
+#[derive(Debug, Clone, Copy)]
+pub enum RowAction {
+    Remove,
+    /// Toggles the multi-selection of logs waiting for approval
+    Select,
+    Approve,
+    Reject,
+    /// Toggles a target of a log waiting for approval
+    Toggle(Target),
+    /// Opens the detail panel, fetching the details if needed
+    Details,
+}
 
-    fn render_dpsreport(&self, ui: &Ui) {
-        thread_local! {
-            static TS: Cell<Instant> = Cell::new(Instant::now());
-        }
-        let Some(tex) = get_texture(DPSREPORT) else {
-            return;
-        };
+/// Rendering of a log in the table. The log itself lives in the core crate.
+pub trait LogExt {
+    fn render_row(
+        &self,
+        ui: &Ui,
+        columns: &[Column],
+        settings: &Settings,
+        selected: bool,
+    ) -> Option<RowAction>;
+    fn render_hovered(&self, ui: &Ui, theme: &Theme);
+}
 
-        // TODO errorhandling
-        match &self.dpsreport {
-            Step::Done(dpsreport) => {
-                let push_id =
-                    ui.push_id(format!("{}btn_dpsreport", self.location.display()).as_str());
-                if ImageButton::new(tex.id(), [16.0, 16.0])
-                    .frame_padding(0)
-                    .build(ui)
-                {
-                    if let Err(e) = open::that_detached(&dpsreport.permalink) {
-                        log::error!("Failed to open browser: {e}");
+impl LogExt for Log {
+    fn render_row(
+        &self,
+        ui: &Ui,
+        columns: &[Column],
+        settings: &Settings,
+        selected: bool,
+    ) -> Option<RowAction> {
+        let mut action = None;
+        for c in columns {
+            ui.table_next_column();
+            match c {
+                Column::Encounter => {
+                    if self.is_awaiting_approval() {
+                        let mut checked = selected;
+                        let label = format!("##select{}", self.location.display());
+                        if ui.checkbox(label, &mut checked) {
+                            action = Some(RowAction::Select);
+                        }
+                        ui.same_line();
                     }
-                }
-                push_id.end();
-                if ui.is_item_hovered() {
-                    ui.tooltip_text(e("Open log in Browser (Rightclick to copy)"));
-                    if ui.is_mouse_clicked(MouseButton::Right) {
-                        // replace with url
-                        ui.set_clipboard_text(&dpsreport.permalink);
+                    let hovered = if let Step::Done(evtc) = &self.evtc {
+                        render_title(self, ui, evtc, &settings.theme)
+                    } else {
+                        ui.text(self.basename().as_str());
+                        ui.is_item_hovered()
+                    };
+                    if hovered {
+                        self.render_hovered(ui, &settings.theme);
                     }
-                }
-            }
-            Step::Error(err) => {
-                let mut red = RED;
-                red[3] = 0.3;
-                Image::new(tex.id(), [16.0, 16.0]).tint_col(red).build(ui);
-                if ui.is_item_hovered() {
-                    ui.tooltip_text(e("Error uploading to dps.report: ") + &format!("{err}"));
-                }
-            }
-            Step::Pending | Step::Active | Step::Retry(_) => {
-                Image::new(tex.id(), [16.0, 16.0])
-                    .tint_col([1.0, 1.0, 1.0, pulse(TS.get().elapsed().as_secs_f32())])
-                    .build(ui);
-                if ui.is_item_hovered() {
-                    if let Step::Retry(t) = self.dpsreport {
-                        ui.tooltip_text(
-                            e("Retrying in ")
-                                + &format!("{}", (Instant::now() - t).as_secs())
-                                + " seconds",
-                        );
+                    let popup = format!("{}row_context_menu", self.location.display());
+                    if hovered && ui.is_mouse_clicked(MouseButton::Right) {
+                        ui.open_popup(&popup);
+                    }
+                    ui.popup(&popup, || {
+                        if self.is_awaiting_approval() {
+                            if ui.selectable(e("Upload")) {
+                                action = Some(RowAction::Approve);
+                            }
+                            if ui.selectable(e("Don't upload")) {
+                                action = Some(RowAction::Reject);
+                            }
+                        }
+                        if matches!(self.dpsreport, Step::Done(_))
+                            && ui.selectable(e("Show details"))
+                        {
+                            action = Some(RowAction::Details);
+                        }
+                        if self.is_uploading() && ui.selectable(e("Cancel upload")) {
+                            self.cancel_uploads();
+                        }
+                        if ui.selectable(e("Remove from list")) {
+                            action = Some(RowAction::Remove);
+                        }
+                    });
+                    if self.is_uploading() && !self.is_cancelled() {
+                        render_progress(self, ui);
+                    }
+                    if self.is_awaiting_approval() && matches!(self.evtc, Step::Done(_)) {
+                        ui.same_line();
+                        let label = format!("{}##approve{}", e("Upload"), self.location.display());
+                        if ui.small_button(label) {
+                            action = Some(RowAction::Approve);
+                        }
                     }
-                    ui.tooltip_text(e(if matches!(self.dpsreport, Step::Active) {
-                        "Uploading..."
-                    } else {
-                        "Queued"
-                    }));
                 }
-            }
-            Step::Skipped => {
-                Image::new(tex.id(), [16.0, 16.0])
-                    .tint_col([1.0, 1.0, 1.0, 0.3])
-                    .build(ui);
-                if ui.is_item_hovered() {
-                    ui.tooltip_text(e("Skipped"));
+                Column::Start => ui.text(
+                    self.start_time()
+                        .map(|t| settings.time_format.format(t, settings.time_include_date))
+                        .unwrap_or_default(),
+                ),
+                Column::Duration => ui.text(
+                    self.duration()
+                        .map(|d| summary::format_duration(d.as_secs()))
+                        .unwrap_or_default(),
+                ),
+                Column::Created => ui.text(
+                    self.modified
+                        .map(|t| {
+                            settings
+                                .time_format
+                                .format(t.into(), settings.time_include_date)
+                        })
+                        .unwrap_or_default(),
+                ),
+                Column::DpsReport | Column::Wingman if self.is_awaiting_approval() => {
+                    let target = if matches!(c, Column::DpsReport) {
+                        Target::DpsReport
+                    } else {
+                        Target::Wingman
+                    };
+                    if render_choice(self, ui, target) {
+                        action = Some(RowAction::Toggle(target));
+                    }
                 }
+                Column::DpsReport => render_dpsreport(self, ui, &settings.theme),
+                Column::Wingman => render_wingman(self, ui, &settings.theme),
+                Column::OpenInFolder => render_open_in_folder(self, ui),
             }
         }
+        action
     }
 
-    fn render_open_in_folder(&self, ui: &Ui) {
-        let Some(tex) = get_texture(OPEN_IN_FOLDER) else {
+    fn render_hovered(&self, ui: &Ui, theme: &Theme) {
+        let Step::Done(evtc) = &self.evtc else {
             return;
         };
-        let push_id = ui.push_id(format!("{}open_in_folder_btn", self.location.display()).as_str());
-        if ImageButton::new(tex.id(), [16.0, 16.0])
-            .frame_padding(0)
-            .build(ui)
-        {
-            if let Err(e) = util::open_with_selected(&self.location) {
-                log::error!("Failed to open folder: {e}");
+        ui.tooltip(|| {
+            render_title(self, ui, evtc, theme);
+            let report = match &self.dpsreport {
+                Step::Done(report) => Some(report),
+                _ => None,
+            };
+            // dps.report knows the specializations, uploads listed by a sync have no players
+            let evtc_players = !report.is_some_and(|r| !r.players.is_empty());
+            let table = evtc_players
+                .then(|| ui.begin_table(self.location.to_string_lossy(), 3))
+                .flatten();
+            if let Some(_table) = table {
+                for a in &evtc.players {
+                    ui.table_next_row();
+                    ui.table_next_column();
+                    if let Some(tex) = get_texture(&a.icon) {
+                        Image::new(tex.id(), [16.0, 16.0]).build(ui);
+                    }
+                    ui.table_next_column();
+                    ui.text(a.character_name.as_str());
+                    ui.table_next_column();
+                    ui.text(a.account_name.as_str());
+                }
             }
-        }
-        push_id.end();
-        if ui.is_item_hovered() {
-            ui.tooltip_text(e("Show Log in Folder"));
-        }
+            if let Some(report) = report {
+                render_report(ui, report);
+            }
+            if let Some(options) = &self.dpsreport_options {
+                ui.text_disabled(format!("{} {}", e("dps.report:"), options.describe()));
+            }
+            if let Some(profile) = self.profile.as_deref().filter(|p| !p.is_empty()) {
+                ui.text_disabled(format!("{} {profile}", e("Profile:")));
+            }
+        })
     }
+}
 
-    fn render_wingman(&self, ui: &Ui) {
-        thread_local! {
-            static TS: Cell<Instant> = Cell::new(Instant::now());
+/// What dps.report found out about the encounter, its players replace the ones of the evtc
+fn render_report(ui: &Ui, report: &DpsReportResponse) {
+    let encounter = &report.encounter;
+    let table = (!report.players.is_empty())
+        .then(|| ui.begin_table(format!("{}##players", report.permalink), 4))
+        .flatten();
+    if let Some(_table) = table {
+        for p in report.players.to_vec() {
+            let specialization = p.specialization();
+            ui.table_next_row();
+            ui.table_next_column();
+            if let Some(tex) = get_texture(&icon_identifier(specialization)) {
+                Image::new(tex.id(), [16.0, 16.0]).build(ui);
+            }
+            ui.table_next_column();
+            ui.text(p.character_name.as_str());
+            ui.table_next_column();
+            ui.text(p.display_name.as_str());
+            ui.table_next_column();
+            ui.text_disabled(specialization);
         }
-        let Some(tex) = get_texture(WINGMAN) else {
-            return;
-        };
-        match &self.wingman {
-            Step::Done(wingman) => {
-                Image::new(tex.id(), [16.0, 16.0])
-                    .tint_col(if *wingman {
-                        // dont tint on success
-                        [1.0, 1.0, 1.0, 1.0]
-                    } else {
-                        let mut red = RED;
-                        red[3] = 0.3;
-                        red
-                    })
-                    .build(ui);
-                if ui.is_item_hovered() {
-                    ui.tooltip_text(e(if *wingman {
-                        "Log queued for Wingman"
-                    } else {
-                        "Error queueing for Log"
-                    }));
+    }
+    ui.separator();
+    ui.text(format!(
+        "{} {}",
+        e("Duration:"),
+        summary::format_duration(encounter.duration)
+    ));
+    if encounter.comp_dps > 0 {
+        ui.text(format!("{} {}", e("Squad DPS:"), encounter.comp_dps));
+    }
+    if encounter.number_of_players > 0 {
+        ui.text(e_fill(
+            "Players: {} ({} groups)",
+            &[&encounter.number_of_players, &encounter.number_of_groups],
+        ));
+    }
+    if let Some(time) = report.uploaded_at() {
+        ui.text_disabled(format!("{} {}", e("Uploaded:"), format_time(time)));
+    }
+    if !report.generator.is_empty() {
+        ui.text_disabled(format!(
+            "{} {}, {}, {}",
+            report.generator,
+            report.generator_version,
+            report.evtc.version,
+            e_fill("Game build {}", &[&encounter.gw2_build])
+        ));
+    }
+    if encounter.json_available {
+        ui.text_disabled(e("Elite Insights JSON available"));
+    }
+}
+
+fn render_progress(log: &Log, ui: &Ui) {
+    let progress = log.upload_progress();
+    let mb = |bytes: u64| bytes as f32 / (1024.0 * 1024.0);
+    let overlay = progress
+        .map(|p| format!("{:.1}/{:.1} MB", mb(p.sent), mb(p.total)))
+        .unwrap_or_default();
+    ui.same_line();
+    ProgressBar::new(progress.map_or(0.0, |p| p.fraction()))
+        .size([120.0, ui.text_line_height()])
+        .overlay_text(overlay.as_str())
+        .build(ui);
+    ui.same_line();
+    let push_id = ui.push_id(format!("{}cancel_upload", log.location.display()).as_str());
+    if ui.small_button("x") {
+        log.cancel_uploads();
+    }
+    push_id.end();
+    if ui.is_item_hovered() {
+        ui.tooltip_text(e("Cancel upload"));
+    }
+}
+
+fn render_dpsreport(log: &Log, ui: &Ui, theme: &Theme) {
+    thread_local! {
+        static TS: Cell<Instant> = Cell::new(Instant::now());
+    }
+    let Some(tex) = get_texture(DPSREPORT) else {
+        return;
+    };
+
+    // TODO errorhandling
+    match &log.dpsreport {
+        Step::Done(dpsreport) => {
+            let push_id = ui.push_id(format!("{}btn_dpsreport", log.location.display()).as_str());
+            if ImageButton::new(tex.id(), [16.0, 16.0])
+                .frame_padding(0)
+                .build(ui)
+            {
+                if let Err(e) = open::that_detached(&dpsreport.permalink) {
+                    log::error!("Failed to open browser: {e}");
                 }
             }
-            Step::Skipped => {
-                Image::new(tex.id(), [16.0, 16.0])
-                    .tint_col([1.0, 1.0, 1.0, 0.3])
-                    .build(ui);
-                if ui.is_item_hovered() {
-                    ui.tooltip_text(e("Skipped"));
+            push_id.end();
+            if ui.is_item_hovered() {
+                ui.tooltip_text(e("Open log in Browser (Rightclick to copy)"));
+                if ui.is_mouse_clicked(MouseButton::Right) {
+                    // replace with url
+                    ui.set_clipboard_text(&dpsreport.permalink);
                 }
             }
-            Step::Active | Step::Pending => {
-                Image::new(tex.id(), [16.0, 16.0])
-                    .tint_col([1.0, 1.0, 1.0, pulse(TS.get().elapsed().as_secs_f32())])
-                    .build(ui);
-                if ui.is_item_hovered() {
-                    ui.tooltip_text(e(if matches!(self.wingman, Step::Active) {
-                        "Uploading..."
-                    } else {
-                        "Queued"
-                    }));
-                }
+        }
+        Step::Error(err) => {
+            Image::new(tex.id(), [16.0, 16.0])
+                .tint_col(theme.failure_tint())
+                .build(ui);
+            let hovered = ui.is_item_hovered();
+            theme.failure_marker(ui);
+            if hovered {
+                ui.tooltip_text(e_fill("Error uploading to dps.report: {}", &[err]));
             }
-            Step::Error(err) => {
-                let mut red = RED;
-                red[3] = 0.3;
-                Image::new(tex.id(), [16.0, 16.0]).tint_col(red).build(ui);
-                if ui.is_item_hovered() {
-                    ui.tooltip_text(e("Error uploading to wingman: ") + &format!("{err}"));
+        }
+        Step::Pending if log.waiting => render_waiting(ui, tex.id()),
+        Step::Pending | Step::Active | Step::Retry(_) => {
+            Image::new(tex.id(), [16.0, 16.0])
+                .tint_col([1.0, 1.0, 1.0, pulse(TS.get().elapsed().as_secs_f32())])
+                .build(ui);
+            if ui.is_item_hovered() {
+                if let Step::Retry(t) = log.dpsreport {
+                    let secs = t.saturating_duration_since(Instant::now()).as_secs();
+                    ui.tooltip_text(e_fill("Retrying in {} seconds", &[&secs]));
                 }
+                ui.tooltip_text(e(if matches!(log.dpsreport, Step::Active) {
+                    "Uploading..."
+                } else {
+                    "Queued"
+                }));
             }
-            Step::Retry(_t) => {
-                // Not supported
+        }
+        Step::Skipped => {
+            Image::new(tex.id(), [16.0, 16.0])
+                .tint_col([1.0, 1.0, 1.0, 0.3])
+                .build(ui);
+            if ui.is_item_hovered() {
+                ui.tooltip_text(e("Skipped"));
             }
         }
     }
+}
 
-    pub fn render_row(&self, ui: &Ui) {
-        // Encounter
-        ui.table_next_column();
-        let hovered = if let Step::Done(evtc) = &self.evtc {
-            self.render_title(ui, evtc)
+/// Target toggle of a log waiting for approval. Returns whether it was clicked.
+fn render_choice(log: &Log, ui: &Ui, target: Target) -> bool {
+    // The targets depend on the profile, which needs the parsed log
+    let (Some(approval), Step::Done(_)) = (log.approval, &log.evtc) else {
+        return false;
+    };
+    let texture = match target {
+        Target::DpsReport => DPSREPORT,
+        Target::Wingman => WINGMAN,
+    };
+    let Some(tex) = get_texture(texture) else {
+        return false;
+    };
+    let chosen = approval.get(target);
+    // The profile of the log doesn't upload there, nothing to pick
+    if !log.eligible.get(target) {
+        Image::new(tex.id(), [16.0, 16.0])
+            .tint_col([1.0, 1.0, 1.0, 0.3])
+            .build(ui);
+        if ui.is_item_hovered() {
+            ui.tooltip_text(e("Skipped by the profile"));
+        }
+        return false;
+    }
+    let push_id = ui.push_id(format!("{}choice{}", log.location.display(), target.name()).as_str());
+    let clicked = ImageButton::new(tex.id(), [16.0, 16.0])
+        .frame_padding(0)
+        .tint_col([1.0, 1.0, 1.0, if chosen { 1.0 } else { 0.3 }])
+        .build(ui);
+    push_id.end();
+    if ui.is_item_hovered() {
+        ui.tooltip_text(if chosen {
+            e("Will be uploaded, click to skip")
         } else {
-            ui.text(self.basename().as_str());
-            ui.is_item_hovered()
-        };
-        if hovered {
-            self.render_hovered(ui);
+            e("Will be skipped, click to upload")
+        });
+    }
+    clicked
+}
+
+/// Held back by the schedule, dimmed instead of pulsing since nothing is happening
+fn render_waiting(ui: &Ui, tex: TextureId) {
+    Image::new(tex, [16.0, 16.0])
+        .tint_col([1.0, 1.0, 1.0, 0.5])
+        .build(ui);
+    if ui.is_item_hovered() {
+        ui.tooltip_text(e("Waiting for idle"));
+    }
+}
+
+fn render_open_in_folder(log: &Log, ui: &Ui) {
+    let Some(tex) = get_texture(OPEN_IN_FOLDER) else {
+        return;
+    };
+    let push_id = ui.push_id(format!("{}open_in_folder_btn", log.location.display()).as_str());
+    if ImageButton::new(tex.id(), [16.0, 16.0])
+        .frame_padding(0)
+        .build(ui)
+    {
+        if let Err(e) = util::open_with_selected(&log.location) {
+            log::error!("Failed to open folder: {e}");
         }
-        // Timestamp
-        ui.table_next_column();
-        ui.text(
-            self.location
-                .metadata()
-                .ok()
-                .and_then(|m| m.modified().ok())
-                .map(format_time)
-                .unwrap_or_default(),
-        );
-        // DpsReport
-        ui.table_next_column();
-        self.render_dpsreport(ui);
-        // Wingman
-        ui.table_next_column();
-        self.render_wingman(ui);
-        // Open in Folder
-        ui.table_next_column();
-        self.render_open_in_folder(ui);
     }
+    push_id.end();
+    if ui.is_item_hovered() {
+        ui.tooltip_text(e("Show Log in Folder"));
+    }
+}
 
-    // Returns wether the text was hovered
-    fn render_title(&self, ui: &Ui, evtc: &Encounter) -> bool {
-        let hovered;
-        if let Step::Done(dpsreport) = &self.dpsreport {
-            let color = if dpsreport.encounter.success {
-                GREEN
-            } else {
-                RED
-            };
-            if let Some(mode) = dpsreport.encounter.format_mode() {
-                if mode == "" {
-                    ui.text_colored(color, format!("{}", dpsreport.encounter.boss));
+fn render_wingman(log: &Log, ui: &Ui, theme: &Theme) {
+    thread_local! {
+        static TS: Cell<Instant> = Cell::new(Instant::now());
+    }
+    let Some(tex) = get_texture(WINGMAN) else {
+        return;
+    };
+    match &log.wingman {
+        Step::Done(wingman) => {
+            Image::new(tex.id(), [16.0, 16.0])
+                .tint_col(if *wingman {
+                    // dont tint on success
+                    [1.0, 1.0, 1.0, 1.0]
                 } else {
-                    ui.text_colored(color, format!("{} ({})", dpsreport.encounter.boss, mode));
-                }
-                hovered = ui.is_item_hovered();
-            } else {
-                ui.text_colored(color, &dpsreport.encounter.boss);
-                // needs to be before the help_marker because it also has a hovered check
-                hovered = ui.is_item_hovered();
-                ui.same_line();
-                ui.help_marker(|| ui.text("Could not determine CM/LCM/NM mode"));
+                    theme.failure_tint()
+                })
+                .build(ui);
+            let hovered = ui.is_item_hovered();
+            if !*wingman {
+                theme.failure_marker(ui);
             }
-        } else {
-            ui.text(format!("{}", BossId::from_header_id(evtc.header.boss_id)));
-            hovered = ui.is_item_hovered();
-        };
-        hovered
+            if hovered {
+                ui.tooltip_text(e(if *wingman {
+                    "Log queued for Wingman"
+                } else {
+                    "Error queueing for Log"
+                }));
+            }
+        }
+        Step::Skipped => {
+            Image::new(tex.id(), [16.0, 16.0])
+                .tint_col([1.0, 1.0, 1.0, 0.3])
+                .build(ui);
+            if ui.is_item_hovered() {
+                ui.tooltip_text(e("Skipped"));
+            }
+        }
+        Step::Pending if log.waiting => render_waiting(ui, tex.id()),
+        Step::Active | Step::Pending => {
+            Image::new(tex.id(), [16.0, 16.0])
+                .tint_col([1.0, 1.0, 1.0, pulse(TS.get().elapsed().as_secs_f32())])
+                .build(ui);
+            if ui.is_item_hovered() {
+                ui.tooltip_text(e(if matches!(log.wingman, Step::Active) {
+                    "Uploading..."
+                } else {
+                    "Queued"
+                }));
+            }
+        }
+        Step::Error(err) => {
+            Image::new(tex.id(), [16.0, 16.0])
+                .tint_col(theme.failure_tint())
+                .build(ui);
+            let hovered = ui.is_item_hovered();
+            theme.failure_marker(ui);
+            if hovered {
+                ui.tooltip_text(e_fill("Error uploading to wingman: {}", &[err]));
+            }
+        }
+        Step::Retry(_t) => {
+            // Not supported
+        }
     }
+}
 
-    pub fn render_hovered(&self, ui: &Ui) {
-        let Step::Done(evtc) = &self.evtc else {
-            return;
-        };
-        ui.tooltip(|| {
-            self.render_title(ui, evtc);
-            if let Some(_table) = ui.begin_table(self.location.to_string_lossy(), 3) {
-                for a in evtc.agents.iter().filter(|a| !a.account_name.is_empty()) {
-                    ui.table_next_row();
-                    ui.table_next_column();
-                    if let Some(tex) = get_texture(identifier_from_agent(a)) {
-                        Image::new(tex.id(), [16.0, 16.0]).build(ui);
-                    }
-                    ui.table_next_column();
-                    ui.text(a.character_name.as_str());
-                    ui.table_next_column();
-                    ui.text(a.account_name.as_str());
-                }
+// Returns wether the text was hovered
+fn render_title(log: &Log, ui: &Ui, evtc: &EncounterSummary, theme: &Theme) -> bool {
+    let hovered;
+    if let Step::Done(dpsreport) = &log.dpsreport {
+        let success = dpsreport.encounter.success;
+        let color = theme.color(success);
+        // dps.report doesn't always know the mode, fall back to what we found in the evtc
+        let mode = dpsreport
+            .encounter
+            .format_mode()
+            .or_else(|| log.outcome.as_ref().and_then(Outcome::format_mode));
+        if let Some(mode) = mode {
+            let boss = &dpsreport.encounter.boss;
+            if mode == "" {
+                ui.text_colored(color, theme.label(success, boss));
+            } else {
+                ui.text_colored(color, theme.label(success, format!("{boss} ({mode})")));
             }
-        })
-    }
+            hovered = ui.is_item_hovered();
+        } else {
+            ui.text_colored(color, theme.label(success, &dpsreport.encounter.boss));
+            // needs to be before the help_marker because it also has a hovered check
+            hovered = ui.is_item_hovered();
+            ui.same_line();
+            ui.help_marker(|| ui.text(e("Could not determine CM/LCM/NM mode")));
+        }
+    } else if let Some(outcome) = &log.outcome {
+        let color = theme.color(outcome.success);
+        let boss = e(&BossId::from_header_id(evtc.boss_id).to_string());
+        let mode = outcome.format_mode().unwrap_or_default();
+        if mode.is_empty() {
+            ui.text_colored(color, theme.label(outcome.success, boss));
+        } else {
+            ui.text_colored(
+                color,
+                theme.label(outcome.success, format!("{boss} ({mode})")),
+            );
+        }
+        hovered = ui.is_item_hovered();
+    } else {
+        ui.text(e(&BossId::from_header_id(evtc.boss_id).to_string()));
+        hovered = ui.is_item_hovered();
+    };
+    hovered
 }
@@ -49,6 +49,11 @@ pub const EVOKER: &str = "UPLOADER_EVOKER_16x16";
 pub const CONDUIT: &str = "UPLOADER_CONDUIT_16x16";
 pub const ANTIQUARY: &str = "UPLOADER_ANTIQUARY_16x16";
 pub const AMALGAM: &str = "UPLOADER_AMALGAM_16x16";
+pub const QUICKACCESS_IDLE: &str = "UPLOADER_QUICKACCESS_IDLE";
+pub const QUICKACCESS_HOVER: &str = "UPLOADER_QUICKACCESS_HOVER";
+pub const QUICKACCESS_UPLOADING: &str = "UPLOADER_QUICKACCESS_UPLOADING";
+pub const QUICKACCESS_ERROR: &str = "UPLOADER_QUICKACCESS_ERROR";
+pub const QUICKACCESS_PAUSED: &str = "UPLOADER_QUICKACCESS_PAUSED";
 pub fn init_textures() {
     load_texture_from_memory(DEADEYE, include_bytes!("../assets/deadeye_16x16.png"), None);
     load_texture_from_memory(HERALD, include_bytes!("../assets/herald_16x16.png"), None);
@@ -99,4 +104,9 @@ pub fn init_textures() {
     load_texture_from_memory(CONDUIT, include_bytes!("../assets/conduit_16x16.png"), None);
     load_texture_from_memory(ANTIQUARY, include_bytes!("../assets/antiquary_16x16.png"), None);
     load_texture_from_memory(AMALGAM, include_bytes!("../assets/amalgam_16x16.png"), None);
+    load_texture_from_memory(QUICKACCESS_IDLE, include_bytes!("../assets/quickaccess_idle.png"), None);
+    load_texture_from_memory(QUICKACCESS_HOVER, include_bytes!("../assets/quickaccess_hover.png"), None);
+    load_texture_from_memory(QUICKACCESS_UPLOADING, include_bytes!("../assets/quickaccess_uploading.png"), None);
+    load_texture_from_memory(QUICKACCESS_ERROR, include_bytes!("../assets/quickaccess_error.png"), None);
+    load_texture_from_memory(QUICKACCESS_PAUSED, include_bytes!("../assets/quickaccess_paused.png"), None);
 }
@@ -0,0 +1,144 @@
+use std::cell::Cell;
+
+use log_uploader_core::{
+    arcdpslog::{Log, Step},
+    eijson::{BOONS, EiJson},
+    summary::format_duration,
+    theme::Theme,
+};
+use nexus::imgui::{
+    TableColumnFlags, TableColumnSetup, TableFlags, TableToken, TreeNodeFlags, Ui, Window,
+};
+
+use crate::util::e;
+
+thread_local! {
+    /// Id of the log shown in the detail panel
+    static SHOWN: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Opens the panel for the log, its details have to be requested separately
+pub fn show(id: usize) {
+    SHOWN.set(Some(id));
+}
+
+pub fn render(ui: &Ui, logs: &[Log], theme: &Theme) {
+    let Some(id) = SHOWN.get() else {
+        return;
+    };
+    // The log was removed from the list
+    let Some(log) = logs.iter().find(|l| l.id == id) else {
+        SHOWN.set(None);
+        return;
+    };
+    let mut opened = true;
+    if let Some(_w) = Window::new(format!("{}##logdetails", e("Log Details")))
+        .opened(&mut opened)
+        .collapsible(false)
+        .begin(ui)
+    {
+        match &log.ei_json {
+            Step::Done(json) => render_json(ui, json),
+            Step::Error(err) => {
+                ui.text_colored(theme.failure(), theme.label(false, err.to_string()))
+            }
+            _ if !matches!(log.dpsreport, Step::Done(_)) => {
+                ui.text(e("Waiting for the dps.report upload..."))
+            }
+            _ => ui.text(e("Fetching details from dps.report...")),
+        }
+    }
+    if !opened {
+        SHOWN.set(None);
+    }
+}
+
+fn begin_table<const N: usize>(ui: &Ui, id: &str, names: [String; N]) -> Option<TableToken<'_>> {
+    ui.begin_table_header_with_flags(
+        id,
+        names.map(|name| TableColumnSetup {
+            name,
+            flags: TableColumnFlags::WIDTH_STRETCH,
+            init_width_or_weight: 0.0,
+            user_id: Default::default(),
+        }),
+        TableFlags::BORDERS_OUTER | TableFlags::BORDERS_INNER_V | TableFlags::ROW_BG,
+    )
+}
+
+fn render_json(ui: &Ui, json: &EiJson) {
+    ui.text(format!(
+        "{} ({})",
+        json.fight_name,
+        format_duration(json.duration_ms / 1000)
+    ));
+    let players = json.dps_ranking();
+
+    if ui.collapsing_header(e("Damage"), TreeNodeFlags::DEFAULT_OPEN) {
+        let columns = [
+            "#".to_string(),
+            e("Character"),
+            e("Account"),
+            e("Profession"),
+            e("DPS"),
+        ];
+        if let Some(_t) = begin_table(ui, "details dps", columns) {
+            for (i, p) in players.iter().enumerate() {
+                ui.table_next_column();
+                ui.text((i + 1).to_string());
+                ui.table_next_column();
+                ui.text(&p.name);
+                ui.table_next_column();
+                ui.text(&p.account);
+                ui.table_next_column();
+                ui.text(&p.profession);
+                ui.table_next_column();
+                ui.text(p.target_dps().to_string());
+            }
+        }
+    }
+
+    if ui.collapsing_header(e("Boon generation"), TreeNodeFlags::empty()) {
+        let mut columns = [const { String::new() }; BOONS.len() + 1];
+        columns[0] = e("Character");
+        for (column, (_, name, _)) in columns[1..].iter_mut().zip(BOONS) {
+            *column = e(name);
+        }
+        if let Some(_t) = begin_table(ui, "details boons", columns) {
+            for p in &players {
+                ui.table_next_column();
+                ui.text(&p.name);
+                for (id, _, intensity) in BOONS {
+                    ui.table_next_column();
+                    let generation = p.boon_generation(id);
+                    if intensity {
+                        ui.text(format!("{generation:.1}"));
+                    } else {
+                        ui.text(format!("{generation:.0}%"));
+                    }
+                }
+            }
+        }
+        ui.text_disabled(e("Given to the squad, stacks for Might and Stability"));
+    }
+
+    if ui.collapsing_header(e("Mechanic failures"), TreeNodeFlags::empty()) {
+        let mechanics = json.failures_per_player();
+        if mechanics.is_empty() {
+            ui.text(e("Nobody failed a mechanic."));
+            return;
+        }
+        if let Some(_t) = begin_table(ui, "details mechanics", [e("Character"), e("Mechanics")]) {
+            for (player, counts) in mechanics {
+                ui.table_next_column();
+                ui.text(player);
+                ui.table_next_column();
+                let counts: Vec<_> = counts
+                    .iter()
+                    .map(|(name, count)| format!("{name} x{count}"))
+                    .collect();
+                ui.text_wrapped(counts.join(", "));
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::{
+    ffi::{CStr, CString, c_char, c_void},
+    path::{Path, PathBuf},
+    ptr,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use log_uploader_core::arcdpslog::{Log, Step};
+use nexus::{
+    event::{event_raise, event_subscribe},
+    event_consume,
+};
+
+// Documented in the README, keep it in sync
+/// Raised once the evtc of a new log was parsed
+pub const EV_LOG_PARSED: &str = "LOG_UPLOADER_LOG_PARSED";
+/// Raised when a target accepted the log
+pub const EV_UPLOAD_FINISHED: &str = "LOG_UPLOADER_UPLOAD_FINISHED";
+/// Raised when a target gave up on the log
+pub const EV_UPLOAD_FAILED: &str = "LOG_UPLOADER_UPLOAD_FAILED";
+/// Inbound: payload is a null terminated utf-8 path of a log to upload
+pub const EV_ENQUEUE: &str = "LOG_UPLOADER_ENQUEUE";
+
+/// Raised by Nexus for every arcdps combat event of the local player, see [`CombatData`]
+const EV_ARCDPS_COMBAT_LOCAL: &str = "EV_ARCDPS_COMBATEVENT_LOCAL_RAW";
+
+/// Bumped whenever fields are added to [`UploadEvent`]. New fields are only ever appended.
+pub const PAYLOAD_VERSION: u32 = 1;
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy)]
+pub enum Target {
+    /// Used for [`EV_LOG_PARSED`]
+    None = 0,
+    DpsReport = 1,
+    Wingman = 2,
+}
+
+pub const SUCCESS_UNKNOWN: i8 = -1;
+pub const SUCCESS_FAIL: i8 = 0;
+pub const SUCCESS_KILL: i8 = 1;
+
+pub const WINGMAN_PENDING: i8 = -1;
+pub const WINGMAN_FAILED: i8 = 0;
+pub const WINGMAN_UPLOADED: i8 = 1;
+pub const WINGMAN_SKIPPED: i8 = 2;
+
+/// Payload of all outbound events. The pointers are only valid for the duration of the event
+/// callback, copy the strings if you need them later.
+#[repr(C)]
+#[derive(Debug)]
+pub struct UploadEvent {
+    pub version: u32,
+    pub target: Target,
+    /// Null terminated utf-8 path of the log file
+    pub path: *const c_char,
+    /// 0 if the log wasn't parsed
+    pub boss_id: u16,
+    /// One of the `SUCCESS_*` constants
+    pub success: i8,
+    /// One of the `WINGMAN_*` constants
+    pub wingman: i8,
+    /// Null terminated dps.report url, null if there is none (yet)
+    pub permalink: *const c_char,
+}
+
+pub fn raise(identifier: &str, target: Target, log: &Log) {
+    let path = CString::new(log.location.to_string_lossy().as_bytes()).unwrap_or_default();
+    let permalink = match &log.dpsreport {
+        Step::Done(r) => CString::new(r.permalink.as_str()).ok(),
+        _ => None,
+    };
+    let payload = UploadEvent {
+        version: PAYLOAD_VERSION,
+        target,
+        path: path.as_ptr(),
+        boss_id: match &log.evtc {
+            Step::Done(evtc) => evtc.boss_id,
+            _ => 0,
+        },
+        success: match log.success() {
+            Some(true) => SUCCESS_KILL,
+            Some(false) => SUCCESS_FAIL,
+            None => SUCCESS_UNKNOWN,
+        },
+        wingman: match log.wingman {
+            Step::Done(true) => WINGMAN_UPLOADED,
+            Step::Done(false) | Step::Error(_) => WINGMAN_FAILED,
+            Step::Skipped => WINGMAN_SKIPPED,
+            _ => WINGMAN_PENDING,
+        },
+        permalink: permalink.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+    };
+    // Nexus calls all subscribers before returning, so the strings outlive every callback
+    unsafe { event_raise(identifier, &payload as *const UploadEvent as *const c_void) };
+}
+
+// Paths other addons asked us to upload, drained by the render thread
+static ENQUEUED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Other addons can send anything, only existing arcdps logs are uploaded
+fn is_log_file(path: &Path) -> bool {
+    let is_evtc = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("zevtc") || e.eq_ignore_ascii_case("evtc"));
+    is_evtc && path.is_file()
+}
+
+/// The parts of the arcdps `cbtevent` we need, everything after `is_statechange` is left out
+// The fields are only there for the layout
+#[allow(dead_code)]
+#[repr(C)]
+struct CombatEvent {
+    time: u64,
+    src_agent: u64,
+    dst_agent: u64,
+    value: i32,
+    buff_dmg: i32,
+    overstack_value: u32,
+    skill_id: u32,
+    src_instid: u16,
+    dst_instid: u16,
+    src_master_instid: u16,
+    dst_master_instid: u16,
+    iff: u8,
+    buff: u8,
+    result: u8,
+    is_activation: u8,
+    is_buffremove: u8,
+    is_ninety: u8,
+    is_fifty: u8,
+    is_moving: u8,
+    is_statechange: u8,
+}
+
+/// arcdps `ag`
+#[allow(dead_code)]
+#[repr(C)]
+struct Agent {
+    name: *const c_char,
+    id: usize,
+    prof: u32,
+    elite: u32,
+    is_self: u32,
+    team: u16,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct CombatData {
+    ev: *const CombatEvent,
+    src: *const Agent,
+    dst: *const Agent,
+    skill_name: *const c_char,
+    id: u64,
+    revision: u64,
+}
+
+const CBTS_ENTERCOMBAT: u8 = 1;
+const CBTS_EXITCOMBAT: u8 = 2;
+
+// Written by the arcdps callback, read by the render thread
+static IN_COMBAT: AtomicBool = AtomicBool::new(false);
+
+pub fn in_combat() -> bool {
+    IN_COMBAT.load(Ordering::Relaxed)
+}
+
+pub fn subscribe() {
+    event_subscribe(
+        EV_ENQUEUE,
+        event_consume!(<c_char> |path| {
+            let Some(path) = path else { return };
+            let path = unsafe { CStr::from_ptr(path) };
+            match path.to_str() {
+                Ok(path) => {
+                    let path = PathBuf::from(path);
+                    if !is_log_file(&path) {
+                        log::warn!("Ignoring enqueued path, not an evtc log: {}", path.display());
+                        return;
+                    }
+                    log::info!("Log enqueued by event: {}", path.display());
+                    ENQUEUED.lock().unwrap().push(path);
+                }
+                Err(e) => log::error!("Enqueued path is not valid utf-8: {e}"),
+            }
+        }),
+    )
+    .revert_on_unload();
+    event_subscribe(
+        EV_ARCDPS_COMBAT_LOCAL,
+        event_consume!(<CombatData> |data| {
+            let Some(data) = data else { return };
+            // Both are null for agent and target changes
+            let (Some(ev), Some(src)) = (unsafe { data.ev.as_ref() }, unsafe { data.src.as_ref() })
+            else {
+                return;
+            };
+            if src.is_self == 0 {
+                return;
+            }
+            match ev.is_statechange {
+                CBTS_ENTERCOMBAT => IN_COMBAT.store(true, Ordering::Relaxed),
+                CBTS_EXITCOMBAT => IN_COMBAT.store(false, Ordering::Relaxed),
+                _ => {}
+            }
+        }),
+    )
+    .revert_on_unload();
+}
+
+pub fn take_enqueued() -> Vec<PathBuf> {
+    std::mem::take(&mut ENQUEUED.lock().unwrap())
+}
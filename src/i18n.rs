@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+
+use log_uploader_core::util::set_translator;
+use nexus::localization::{set_translation, translate};
+
+// English is the source language, the keys double as the english text
+const LOCALES: &[(&str, &str)] = &[
+    ("de", include_str!("../locales/de.json")),
+    ("fr", include_str!("../locales/fr.json")),
+    ("es", include_str!("../locales/es.json")),
+];
+
+pub fn load() {
+    for (lang, json) in LOCALES {
+        match serde_json::from_str::<HashMap<String, String>>(json) {
+            Ok(translations) => {
+                for (key, value) in translations {
+                    set_translation(&key, lang, &value);
+                }
+            }
+            Err(e) => log::error!("Failed to load {lang} translations: {e}"),
+        }
+    }
+    // The core crate doesn't know about nexus
+    set_translator(|s| translate(s));
+}
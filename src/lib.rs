@@ -1,218 +1,77 @@
 use std::{
-    path::{Path, PathBuf},
-    sync::{
-        Mutex,
-        mpsc::{self, Receiver, Sender},
-    },
-    thread::{self},
-    time::{Duration, Instant},
+    cell::{Cell, RefCell},
+    collections::BTreeSet,
+    path::Path,
+    sync::Mutex,
 };
 
-use arcdpslog::Step;
-use common::*;
-use filewatcher::ReceiverExt;
+use arcdpslog::{LogExt, RowAction};
+use log_uploader_core::{
+    arcdpslog::{Log, Step},
+    history, session,
+    settings::Settings,
+    summary::collect_urls,
+    uploader::{Target, Uploader, UploaderEvent},
+};
+use logfilter::LogFilter;
 use nexus::{
     AddonFlags, UpdateProvider,
     gui::{RenderType, register_render},
-    imgui::{ChildWindow, TableColumnFlags, TableColumnSetup, TableFlags, Ui, Window},
+    imgui::{ChildWindow, TreeNodeFlags, Ui, Window},
     keybind::{Keybind, register_keybind_with_struct},
-    keybind_handler,
-    paths::get_addon_dir,
-    render,
+    keybind_handler, render,
 };
-use notify::{Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
-use settings::Settings;
-use util::e;
+use notifications::Notification;
+use util::{e, e_fill};
 
 mod arcdpslog;
 mod assets;
-mod common;
-mod dpsreport;
-mod evtc;
-mod filewatcher;
+mod details;
+mod events;
+mod i18n;
+mod logfilter;
+mod notifications;
+mod quickaccess;
 mod settings;
+mod stats;
+mod table;
+mod theme;
 mod util;
-mod wingman;
 
-// TODO: grep for all the `let _ =` and add error handling
-// TODO: Implement actual dpsreport
-// TODO: Icons
 struct State {
-    producer_rx: Mutex<Option<Receiver<common::WorkerMessage>>>,
-    evtc_worker: Mutex<Option<Sender<evtc::EvtcJob>>>,
-    filewatcher: Mutex<Option<Box<dyn Watcher + Send>>>,
-    file_rx: Mutex<Option<Receiver<Result<Event, notify::Error>>>>,
-    dps_worker: Mutex<Option<Sender<dpsreport::DpsJob>>>,
-    wingman_worker: Mutex<Option<Sender<wingman::WingmanJob>>>,
-    threads: Mutex<Vec<thread::JoinHandle<()>>>,
-    logs: Mutex<Vec<arcdpslog::Log>>,
-}
-
-impl State {
-    /// Returns the receiver for updates from the workers
-    fn try_next_producer(&self) -> Option<common::WorkerMessage> {
-        self.producer_rx
-            .lock()
-            .unwrap()
-            .as_ref()
-            .and_then(|rx| rx.try_recv().ok())
-    }
-
-    fn init_producer(&self) -> Sender<common::WorkerMessage> {
-        let (tx, rx) = mpsc::channel();
-        *self.producer_rx.lock().unwrap() = Some(rx);
-        tx
-    }
-
-    fn init_evtc_worker(&self) -> Receiver<evtc::EvtcJob> {
-        let (tx, rx) = mpsc::channel();
-        *self.evtc_worker.lock().unwrap() = Some(tx);
-        rx
-    }
-
-    fn init_dps_worker(&self) -> Receiver<dpsreport::DpsJob> {
-        let (tx, rx) = mpsc::channel();
-        *self.dps_worker.lock().unwrap() = Some(tx);
-        rx
-    }
-
-    fn init_wingman_worker(&self) -> Receiver<wingman::WingmanJob> {
-        let (tx, rx) = mpsc::channel();
-        *self.wingman_worker.lock().unwrap() = Some(tx);
-        rx
-    }
-
-    fn init_filewatcher(&self, path: PathBuf) {
-        // Maybe instead of the separate receiver, we can just use producer_rx
-        let (tx, rx) = std::sync::mpsc::channel();
-        // unwrap this, this can only fail, if creating the semaphore fails
-        // ReadDirectoryChangesWatcher is really inconsistent on wine, fall back to PollWatcher
-        let mut watcher: Box<dyn Watcher + Send> = if winecheck::is_wine() {
-            Box::new(
-                PollWatcher::new(
-                    tx,
-                    notify::Config::default().with_poll_interval(Duration::from_secs(5)),
-                )
-                .unwrap(),
-            )
-        } else {
-            Box::new(RecommendedWatcher::new(tx, notify::Config::default()).unwrap())
-        };
-        if path.exists() && path.is_dir() {
-            // panics if file doesn't exist, but we just checked. I know toctou but this should be fine
-            // also can panic on channel errors (very unlikely)
-            watcher
-                .watch(&path, notify::RecursiveMode::Recursive)
-                .unwrap();
-        } else {
-            log::warn!(
-                "Could not watch directory. {} does not exist or is not a directory",
-                path.display()
-            );
-        }
-        *self.filewatcher.lock().unwrap() = Some(watcher);
-        *self.file_rx.lock().unwrap() = Some(rx);
-    }
-
-    fn append_thread(&self, handle: thread::JoinHandle<()>) {
-        self.threads.lock().unwrap().push(handle);
-    }
-    pub fn unwatch(&self, path: impl AsRef<Path>) {
-        let path = path.as_ref();
-        if let Some(watcher) = self.filewatcher.lock().unwrap().as_mut() {
-            if let Err(e) = watcher.unwatch(path) {
-                log::error!("Failed to unwatch {}: {e}", path.display());
-            }
-        }
-    }
-    pub fn watch(&self, path: impl AsRef<Path>) {
-        let path = path.as_ref();
-        if let Some(watcher) = self.filewatcher.lock().unwrap().as_mut() {
-            if let Err(e) = watcher.watch(path, RecursiveMode::Recursive) {
-                log::error!("Failed to watch {}: {e}", path.display());
-            }
-        }
-    }
+    uploader: Mutex<Option<Uploader>>,
 }
 
 // This could almost be a thread local but it's required to be safely unloaded which runs on a
 // different thread
 static STATE: State = State {
-    producer_rx: Mutex::new(None),
-    evtc_worker: Mutex::new(None),
-    filewatcher: Mutex::new(None),
-    file_rx: Mutex::new(None),
-    dps_worker: Mutex::new(None),
-    wingman_worker: Mutex::new(None),
-    threads: Mutex::new(Vec::new()),
-    logs: Mutex::new(Vec::new()),
+    uploader: Mutex::new(None),
 };
 const KB_IDENTIFIER: &str = "KB_OPEN_WINGMAN_UPLOADS";
-
-fn collect_urls(logs: &[arcdpslog::Log], settings: &Settings) -> String {
-    let mut urls = vec![];
-    for l in logs {
-        if let Step::Done(ref dpsreport) = l.dpsreport {
-            match (
-                dpsreport.encounter.success,
-                settings.copy_success,
-                settings.copy_failure,
-            ) {
-                (true, false, _) => continue,
-                (false, _, false) => continue,
-                _ => urls.push(format_url(dpsreport, &settings.dpsreport_copyformat)),
-            }
-        }
-    }
-    urls.join("\r\n")
-}
-
-fn format_url(dpsreport: &dpsreport::DpsReportResponse, format_template: &str) -> String {
-    /*
-        @1 - permalink
-        @2 - boss name & mode
-        @3 - boss id
-        @4 - encounter success/fail
-    */
-    let success = match dpsreport.encounter.success {
-        true => "Success",
-        false => "Fail",
-    };
-    let mode = dpsreport
-        .encounter
-        .format_mode()
-        .unwrap_or_else(|| "Unknown".to_string());
-    let cm = if mode == "" {
-        "".to_string()
-    } else {
-        format!(" ({})", mode)
-    };
-
-    return format_template
-        .replace("@1", &dpsreport.permalink)
-        .replace("@2", &format!("{}{}", dpsreport.encounter.boss, cm))
-        .replace("@3", &dpsreport.encounter.boss_id.to_string())
-        .replace("@4", &success);
-}
+const KB_PAUSE_IDENTIFIER: &str = "KB_PAUSE_WINGMAN_UPLOADS";
 
 fn load() {
     log::info!("Loading log-uploader");
     assets::init_textures();
-    // lots of locking and relocking but should be fine, since nothing is running
-    let producer_tx = STATE.init_producer();
+    i18n::load();
     // Todo move failure handling to from_path impl
     Settings::from_path(settings::config_path()).unwrap_or_else(|e| {
         log::error!("Failed to load settings, using default. Error: {e}");
         Settings::get_mut().init();
     });
-    STATE.init_filewatcher(Settings::get().logpath().into());
-    let evtc_rx = STATE.init_evtc_worker();
-    STATE.append_thread(evtc::run(evtc_rx, producer_tx.clone()));
-    let dpsreport_rx = STATE.init_dps_worker();
-    STATE.append_thread(dpsreport::run(dpsreport_rx, producer_tx.clone()));
-    let wingman_rx = STATE.init_wingman_worker();
-    STATE.append_thread(wingman::run(wingman_rx, producer_tx.clone()));
+    history::from_path(settings::history_path()).unwrap_or_else(|e| {
+        log::error!("Failed to load upload history: {e}");
+    });
+    // ReadDirectoryChangesWatcher is really inconsistent on wine, fall back to PollWatcher
+    let uploader = Uploader::new(
+        Some(Path::new(Settings::get().logpath())),
+        winecheck::is_wine(),
+        settings::config_path(),
+        settings::history_path(),
+    );
+    *STATE.uploader.lock().unwrap() = Some(uploader);
 
+    events::subscribe();
     register_render(RenderType::Render, render!(render_fn)).revert_on_unload();
     register_render(RenderType::OptionsRender, render!(render_options)).revert_on_unload();
     register_keybind_with_struct(
@@ -229,241 +88,91 @@ fn load() {
         },
     )
     .revert_on_unload();
+    register_keybind_with_struct(
+        KB_PAUSE_IDENTIFIER,
+        keybind_handler!(|_, is_release| if !is_release {
+            let mut settings = Settings::get_mut();
+            let paused = !settings.paused;
+            settings::set_paused(&mut settings, paused);
+        }),
+        Keybind {
+            // P
+            key: 25,
+            alt: true,
+            ctrl: false,
+            shift: true,
+        },
+    )
+    .revert_on_unload();
+    quickaccess::load();
     log::info!("Loaded log-uploader");
 }
 fn unload() {
     log::info!("Unloading log-uploader");
-    let settings = Settings::get();
-    log::trace!("Unwatching logpath");
-    STATE.unwatch(settings.logpath());
-    drop(settings);
-    log::trace!("Closing channels");
-    drop(STATE.producer_rx.lock().unwrap().take());
-    drop(STATE.evtc_worker.lock().unwrap().take());
-    drop(STATE.filewatcher.lock().unwrap().take());
-    drop(STATE.file_rx.lock().unwrap().take());
-    drop(STATE.dps_worker.lock().unwrap().take());
-    drop(STATE.wingman_worker.lock().unwrap().take());
-
-    log::trace!("Waiting on threads");
-    for t in STATE.threads.lock().unwrap().drain(..) {
-        let threadname = t
-            .thread()
-            .name()
-            .map(String::from)
-            .unwrap_or_else(|| format!("{:?}", t.thread().id()));
-        log::trace!("Waiting on thread {}", threadname);
-        if let Err(e) = t.join() {
-            log::error!("Failed to join thread {}: {:#?}", threadname, e);
-        }
+    quickaccess::unload();
+    if let Some(uploader) = STATE.uploader.lock().unwrap().take() {
+        uploader.shutdown();
     }
-    // Call this to run destructors (free the vec)
-    std::mem::swap(STATE.logs.lock().unwrap().as_mut(), &mut vec![]);
+    history::clear();
     log::trace!("Unloaded");
 }
 
-fn get_new_logs(logs: &mut Vec<arcdpslog::Log>) {
-    let file_rx = STATE.file_rx.lock().unwrap();
-    let Some(file_rx) = file_rx.as_ref() else {
-        return;
-    };
-    while let Ok(iter) = file_rx.next_log() {
-        for l in iter {
-            log::info!("New log found: {}", l.display());
-            logs.push(arcdpslog::Log::new(l));
-        }
-    }
-}
-
-fn update_logs(logs: &mut [arcdpslog::Log]) {
-    while let Some(WorkerMessage { index, payload }) = STATE.try_next_producer() {
-        match payload {
-            WorkerType::Evtc(evtc) => {
-                logs[index].evtc = Step::from_value(evtc);
-            }
-            WorkerType::DpsReport(r) => match r {
-                Ok(Ok(r)) => {
-                    let mut settings = Settings::get_mut();
-                    // If the token changed, we need to update it
-                    // Also persist to disk so user doesn't have to press save in options
-                    // might freeze the game on first log upload after install
-                    if settings.dpsreport_token != r.user_token {
-                        settings.dpsreport_token = r.user_token.clone();
-                        settings.store(settings::config_path()).unwrap_or_else(|e| {
-                            log::error!("Failed to store settings: {e}");
-                        });
-                    };
-                    logs[index].dpsreport = Step::from_value(Ok(r));
-                }
-                Ok(Err(e)) => {
-                    logs[index].dpsreport = Step::Retry(e);
-                }
-                Err(e) => {
-                    logs[index].dpsreport = Step::from_value(Err(e));
-                }
-            },
-            WorkerType::Wingman(r) => {
-                logs[index].wingman = Step::from_value(r);
-            }
-        }
-    }
-}
-
-fn advance_logs(logs: &mut [arcdpslog::Log]) {
-    let evtc_tx = STATE.evtc_worker.lock().unwrap();
-    let Some(evtc_tx) = evtc_tx.as_ref() else {
-        return;
-    };
-    let dps_tx = STATE.dps_worker.lock().unwrap();
-    let Some(dps_tx) = dps_tx.as_ref() else {
-        return;
-    };
-    let wingman_tx = STATE.wingman_worker.lock().unwrap();
-    let Some(wingman_tx) = wingman_tx.as_ref() else {
-        return;
+/// Turns what the uploader reports into alerts and events for other addons
+fn handle_event(event: UploaderEvent) {
+    let notifications = Settings::get().notifications.clone();
+    let event_target = |target| match target {
+        Target::DpsReport => events::Target::DpsReport,
+        Target::Wingman => events::Target::Wingman,
     };
-    // This can easily be extended to support other stuff like discord webhooks
-    for (i, l) in logs.iter_mut().enumerate() {
-        if matches!(l.evtc, Step::Pending) {
-            log::trace!("Activating evtc job for {}", l.location.display());
-            l.evtc = Step::Active;
-            if let Err(e) = evtc_tx.send((i, l.location.clone())) {
-                log::error!("Failed to send evtc job: {e}");
-            }
-        }
-        // cannot do anything else until the evtc is done
-        if matches!(l.evtc, Step::Active) {
-            log::trace!("we still parsing evtc for {}", l.location.display());
-            continue;
-        }
-        if let Step::Error(e) = &l.evtc {
-            log::error!("Failed to parse evtc for {}: {e}", l.location.display());
-            continue;
+    match event {
+        UploaderEvent::Parsed(log) => {
+            events::raise(events::EV_LOG_PARSED, events::Target::None, log);
         }
-        if matches!(l.dpsreport, Step::Pending) {
-            let settings = Settings::get();
-            let enabled = settings.enable_dpsreport();
-            let token = settings.dpsreport_token.clone();
-            let Step::Done(ref enc) = l.evtc else {
-                unreachable!()
-            };
-            if enabled && !settings.filter_dpsreport.contains(&enc.header.boss_id) {
-                l.dpsreport = Step::Active;
-                if let Err(e) = dps_tx.send((i, l.location.clone(), token)) {
-                    log::error!("Failed to send dpsreport job: {e}");
-                }
-            } else {
-                l.dpsreport = Step::Skipped;
-            }
-        }
-        if matches!(l.wingman, Step::Pending) {
-            let settings = Settings::get();
-            let enabled = settings.enable_wingman;
-            let Step::Done(ref enc) = l.evtc else {
-                unreachable!()
-            };
-            if enabled
-                && enc.header.boss_id != 1
-                && !settings.filter_wingman.contains(&enc.header.boss_id)
-            {
-                // I wonder if I can do this without the if check since this is guaranteed to be
-                // done
-                l.wingman = Step::Active;
-                if let Err(e) = wingman_tx.send((
-                    i,
-                    l.location.clone(),
-                    // Error handling on missing pov (broken log?)
-                    enc.pov.clone().map(|a| a.account_name).unwrap_or_default(),
-                    enc.header.boss_id,
-                )) {
-                    log::error!("Failed to send wingman job: {e}");
+        UploaderEvent::Uploaded(target, log) => {
+            if let (Target::DpsReport, Step::Done(r)) = (target, &log.dpsreport) {
+                if r.encounter.success {
+                    Notification::Kill {
+                        boss: &r.encounter.boss,
+                        permalink: &r.permalink,
+                    }
+                    .send(&notifications);
                 }
-            } else {
-                l.wingman = Step::Skipped;
             }
+            events::raise(events::EV_UPLOAD_FINISHED, event_target(target), log);
         }
-        if let Step::Retry(t) = l.dpsreport {
-            if l.dpsreport_count > 3 {
-                l.dpsreport = Step::Error(anyhow::anyhow!("Retry limit reached"));
-            } else if Instant::now() > t {
-                l.dpsreport_count += 1;
-                l.dpsreport = Step::Pending;
+        UploaderEvent::Failed(target, log) => {
+            Notification::UploadFailed {
+                target: target.name(),
+                boss: &log.boss_name(),
             }
+            .send(&notifications);
+            events::raise(events::EV_UPLOAD_FAILED, event_target(target), log);
         }
-    }
-}
-
-fn setup_table<F: FnOnce()>(ui: &Ui, f: F) {
-    let flags =
-        TableFlags::BORDERS_OUTER | TableFlags::BORDERS_INNER_V | TableFlags::NO_PAD_INNER_X;
-
-    let max_time_width = ui.calc_text_size("00:00 (Thu Nov 14)")[0];
-    let max_path_width = ui.calc_text_size("Kanaxai, Scythe of House Aurkus (25577)")[0];
-
-    let t = ui.begin_table_header_with_flags(
-        e("Uploads"),
-        [
-            TableColumnSetup {
-                name: e("Encounter"),
-                flags: TableColumnFlags::WIDTH_STRETCH,
-                init_width_or_weight: max_path_width + 10.0,
-                user_id: Default::default(),
-            },
-            TableColumnSetup {
-                name: e("Created"),
-                flags: TableColumnFlags::WIDTH_FIXED,
-                init_width_or_weight: max_time_width + 10.0,
-                user_id: Default::default(),
-            },
-            TableColumnSetup {
-                // DpsReport
-                name: e(""),
-                flags: TableColumnFlags::WIDTH_FIXED,
-                init_width_or_weight: 20.0,
-                user_id: Default::default(),
-            },
-            TableColumnSetup {
-                // Wingman
-                name: e(""),
-                flags: TableColumnFlags::WIDTH_FIXED,
-                init_width_or_weight: 20.0,
-                user_id: Default::default(),
-            },
-            TableColumnSetup {
-                // Open in Folder
-                name: e(""),
-                flags: TableColumnFlags::WIDTH_FIXED,
-                init_width_or_weight: 20.0,
-                user_id: Default::default(),
-            },
-        ],
-        flags,
-    );
-    if let Some(t) = t {
-        f();
-        t.end();
+        UploaderEvent::RateLimited => Notification::RateLimited.send(&notifications),
+        UploaderEvent::WatcherLost(path) => Notification::WatcherLost { path }.send(&notifications),
+        UploaderEvent::Offline => Notification::Offline.send(&notifications),
+        UploaderEvent::Online => Notification::Online.send(&notifications),
     }
 }
 
 // Notification window for misspelled logpath (hotfix 20241114)
-fn render_hotfix20241114(ui: &Ui, settings: &mut Settings) {
+fn render_hotfix20241114(ui: &Ui, settings: &mut Settings, uploader: &mut Uploader) {
     if settings.check_hotfix20241114() && !settings.hide_hotfix_notification_20241114 {
         if let Some(_w) = Window::new(e("Arcdps Path Fix"))
             .collapsible(false)
             .begin(ui)
         {
-            ui.text(format!(
+            ui.text(e_fill(
                 r#"Unfortunately there was a typo in the default logpath last version.
 You seem to have the wrong logpath ({}) configured.
 Do you want to set it to the default logpath ({})?
 You can also hide this message permanently if the configured path is correct."#,
-                settings.logpath,
-                Settings::default_dir().display()
+                &[&settings.logpath, &Settings::default_dir().display()],
             ));
             if ui.button(e("Reset logpath to default")) {
-                STATE.unwatch(&settings.logpath);
+                uploader.unwatch(&settings.logpath);
                 settings.fix_hotfix20241114();
-                STATE.watch(&settings.logpath);
+                uploader.watch(&settings.logpath);
             }
             if ui.button(e("Don't show this window again")) {
                 settings.hide_hotfix_notification_20241114 = true;
@@ -472,58 +181,235 @@ You can also hide this message permanently if the configured path is correct."#,
     }
 }
 
+thread_local! {
+    // ids of the logs waiting for approval the user picked
+    static SELECTED: RefCell<BTreeSet<usize>> = const { RefCell::new(BTreeSet::new()) };
+}
+
 fn render_fn(ui: &Ui) {
-    let mut logs = STATE.logs.lock().unwrap();
-    get_new_logs(&mut logs);
-    advance_logs(&mut logs);
-    update_logs(&mut logs);
+    thread_local! {
+        // 0 means all sessions
+        static SELECTED_SESSION: Cell<usize> = const { Cell::new(0) };
+        static FILTER: RefCell<LogFilter> = const { RefCell::new(LogFilter::new()) };
+    }
+    let mut uploader = STATE.uploader.lock().unwrap();
+    let Some(uploader) = uploader.as_mut() else {
+        return;
+    };
+    for path in events::take_enqueued() {
+        uploader.enqueue(path);
+    }
+    uploader.set_in_combat(events::in_combat());
+    uploader.tick(handle_event);
 
     let mut settings = Settings::get_mut();
-    render_hotfix20241114(ui, &mut settings);
+    render_hotfix20241114(ui, &mut settings, uploader);
+    quickaccess::update(&uploader.logs, settings.paused, uploader.is_offline());
+    let logs = &mut uploader.logs;
+    let mut actions = vec![];
     if settings.show_window {
         if let Some(_w) = Window::new(e("Log Uploader"))
             .opened(&mut settings.show_window)
             .collapsible(false)
             .begin(ui)
         {
+            let sessions = session::split(logs, settings.session_gap());
+            let filter = FILTER.with_borrow_mut(|filter| {
+                filter.render(ui);
+                filter.clone()
+            });
+            let awaiting = logs.iter().any(Log::is_awaiting_approval);
+            let control_rows = if awaiting { 3.0 } else { 2.0 };
             ChildWindow::new("Log Table")
-                .size([0.0, -ui.frame_height_with_spacing() * 2.0])
+                .size([0.0, -ui.frame_height_with_spacing() * control_rows])
                 .always_auto_resize(true)
                 .build(ui, || {
                     if logs.is_empty() {
                         ui.text(e("No logs yet."));
                         return;
                     }
-                    setup_table(ui, || {
+                    let columns = settings.columns.visible();
+                    table::setup_table(ui, &columns, &settings, |sort| {
+                        let mut order: Vec<_> = sessions.iter().enumerate().collect();
                         if settings.rev_log_order {
-                            for l in logs.iter().rev() {
-                                l.render_row(ui);
+                            order.reverse();
+                        }
+                        for (i, s) in order {
+                            let mut rows: Vec<_> = logs[s.logs.clone()]
+                                .iter()
+                                .filter(|l| filter.matches(l))
+                                .collect();
+                            if rows.is_empty() && filter.is_active() {
+                                continue;
+                            }
+                            match sort {
+                                Some(sort) => rows.sort_by(|a, b| sort.compare(a, b)),
+                                None if settings.rev_log_order => rows.reverse(),
+                                None => {}
                             }
-                        } else {
-                            for l in logs.iter() {
-                                l.render_row(ui);
+                            ui.table_next_row();
+                            ui.table_next_column();
+                            if !ui.collapsing_header(
+                                format!("{}##session{i}", s.label(i)),
+                                TreeNodeFlags::DEFAULT_OPEN,
+                            ) {
+                                continue;
+                            }
+                            // rows start with table_next_column, so finish the header row here
+                            ui.table_next_row();
+                            for l in rows {
+                                let selected = SELECTED.with_borrow(|s| s.contains(&l.id));
+                                if let Some(action) =
+                                    l.render_row(ui, &columns, &settings, selected)
+                                {
+                                    actions.push((l.id, action));
+                                }
                             }
                         }
                     });
                 });
 
             let controls = ui.begin_group();
+            if awaiting {
+                render_approval(ui, logs);
+            }
             ui.align_text_to_frame_padding();
             ui.text(e("Include:"));
             ui.same_line();
-            ui.checkbox("Success", &mut settings.copy_success);
+            ui.checkbox(e("Success"), &mut settings.copy_success);
             ui.same_line();
-            ui.checkbox("Failure", &mut settings.copy_failure);
+            ui.checkbox(e("Failure"), &mut settings.copy_failure);
+            ui.same_line();
+            ui.checkbox(e("Summary"), &mut settings.copy_summary);
+            if ui.is_item_hovered() {
+                ui.tooltip_text(e(
+                    "Group logs by wing/strike/fractal and only copy the kill (or last attempt) per boss",
+                ));
+            }
+            if !settings.profiles.is_empty() {
+                render_profile_switcher(ui, &mut settings);
+            }
 
+            let mut selected = SELECTED_SESSION.get().min(sessions.len());
+            let mut items = vec![e("All sessions")];
+            items.extend(sessions.iter().enumerate().map(|(i, s)| s.label(i)));
             if ui.button(e("Copy dps.report urls")) {
-                let urls = collect_urls(&logs, &settings);
+                let logs = match selected {
+                    0 => &logs[..],
+                    n => &logs[sessions[n - 1].logs.clone()],
+                };
+                let urls = collect_urls(logs, &settings);
                 if !urls.is_empty() {
                     ui.set_clipboard_text(urls);
                 }
             }
+            ui.same_line();
+            if ui.button(e("Clear finished")) {
+                logs.retain(|l| !l.is_finished());
+            }
+            ui.same_line();
+            let pause_label = if settings.paused {
+                e("Resume uploads")
+            } else {
+                e("Pause uploads")
+            };
+            if ui.button(pause_label) {
+                let paused = !settings.paused;
+                settings::set_paused(&mut settings, paused);
+            }
+            ui.same_line();
+            if ui.button(e("Statistics")) {
+                settings.show_statistics = !settings.show_statistics;
+            }
+            ui.same_line();
+            ui.set_next_item_width(ui.content_region_avail()[0]);
+            ui.combo_simple_string("##copysession", &mut selected, &items);
+            SELECTED_SESSION.set(selected);
             controls.end();
         }
     }
+    apply_row_actions(logs, actions);
+    details::render(ui, logs, &settings.theme);
+    if settings.show_statistics {
+        let sessions = session::split(logs, settings.session_gap());
+        if stats::render(ui, &mut settings.show_statistics, uploader, sessions.last()) {
+            uploader.sync_history(&settings.active_profile, &settings.active().dpsreport_token);
+        }
+    }
+}
+
+/// Quick switch between the profiles, the automatic selection still wins for matching logs
+fn render_profile_switcher(ui: &Ui, settings: &mut Settings) {
+    let mut names = vec![e("Default")];
+    names.extend(settings.profiles.iter().map(|p| p.name.clone()));
+    let mut selected = settings
+        .profiles
+        .iter()
+        .position(|p| p.name == settings.active_profile)
+        .map_or(0, |i| i + 1);
+    ui.same_line();
+    ui.set_next_item_width(150.0);
+    if ui.combo_simple_string(e("Profile##switchprofile"), &mut selected, &names) {
+        let name = match selected {
+            0 => String::new(),
+            n => settings.profiles[n - 1].name.clone(),
+        };
+        settings::set_profile(settings, name);
+    }
+}
+
+/// Bulk approval of logs waiting for it, the multi-selection is made in the table
+fn render_approval(ui: &Ui, logs: &mut [Log]) {
+    let selected = SELECTED.with_borrow(|s| s.len());
+    ui.align_text_to_frame_padding();
+    ui.text(e("Awaiting approval:"));
+    ui.same_line();
+    if ui.button(format!("{} ({selected})", e("Upload selected"))) {
+        let selected = SELECTED.take();
+        logs.iter_mut()
+            .filter(|l| selected.contains(&l.id))
+            .for_each(Log::approve);
+    }
+    ui.same_line();
+    if ui.button(e("Upload all kills")) {
+        logs.iter_mut()
+            .filter(|l| l.is_awaiting_approval() && l.success() == Some(true))
+            .for_each(Log::approve);
+    }
+    ui.same_line();
+    if ui.button(e("Skip all")) {
+        logs.iter_mut()
+            .filter(|l| l.is_awaiting_approval())
+            .for_each(Log::reject);
+    }
+}
+
+fn apply_row_actions(logs: &mut Vec<Log>, actions: Vec<(usize, RowAction)>) {
+    for (id, action) in actions {
+        let index = logs.iter().position(|l| l.id == id);
+        match (action, index) {
+            (RowAction::Select, _) => SELECTED.with_borrow_mut(|s| {
+                if !s.remove(&id) {
+                    s.insert(id);
+                }
+            }),
+            (_, None) => {}
+            (RowAction::Remove, Some(i)) => {
+                logs.remove(i);
+            }
+            (RowAction::Details, Some(i)) => {
+                logs[i].request_ei_json();
+                details::show(id);
+            }
+            (RowAction::Approve, Some(i)) => logs[i].approve(),
+            (RowAction::Reject, Some(i)) => logs[i].reject(),
+            (RowAction::Toggle(target), Some(i)) => logs[i].toggle_approval(target),
+        }
+    }
+    // Logs which were approved or removed can't be selected anymore
+    SELECTED.with_borrow_mut(|s| {
+        s.retain(|id| logs.iter().any(|l| l.id == *id && l.is_awaiting_approval()))
+    });
 }
 
 fn render_options(ui: &Ui) {
@@ -531,8 +417,10 @@ fn render_options(ui: &Ui) {
     settings::render(ui);
     let new = Settings::get().clone();
     if old.logpath != new.logpath {
-        STATE.unwatch(old.logpath);
-        STATE.watch(new.logpath);
+        if let Some(uploader) = STATE.uploader.lock().unwrap().as_mut() {
+            uploader.unwatch(old.logpath);
+            uploader.watch(new.logpath);
+        }
     }
 }
 
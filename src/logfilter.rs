@@ -0,0 +1,139 @@
+use log_uploader_core::arcdpslog::{Log, Step};
+use nexus::imgui::Ui;
+
+use crate::util::e;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+    Any,
+    Queued,
+    Uploading,
+    Done,
+    Failed,
+    Skipped,
+}
+
+impl StatusFilter {
+    const ALL: [StatusFilter; 6] = [
+        Self::Any,
+        Self::Queued,
+        Self::Uploading,
+        Self::Done,
+        Self::Failed,
+        Self::Skipped,
+    ];
+
+    fn name(self) -> String {
+        match self {
+            Self::Any => e("Any"),
+            Self::Queued => e("Queued"),
+            Self::Uploading => e("Uploading"),
+            Self::Done => e("Uploaded"),
+            Self::Failed => e("Failed"),
+            Self::Skipped => e("Skipped"),
+        }
+    }
+
+    /// `failed` is for targets which can finish without succeeding (wingman)
+    fn matches<T>(self, step: &Step<T>, failed: bool) -> bool {
+        match (self, step) {
+            (Self::Any, _) => true,
+            (Self::Queued, Step::Pending | Step::Retry(_)) => true,
+            (Self::Uploading, Step::Active) => true,
+            (Self::Done, Step::Done(_)) => !failed,
+            (Self::Failed, Step::Done(_)) => failed,
+            (Self::Failed, Step::Error(_)) => true,
+            (Self::Skipped, Step::Skipped) => true,
+            _ => false,
+        }
+    }
+
+    fn render(ui: &Ui, label: String, filter: &mut StatusFilter) {
+        let mut i = Self::ALL
+            .iter()
+            .position(|f| f == filter)
+            .unwrap_or_default();
+        let names = Self::ALL.map(Self::name);
+        if ui.combo_simple_string(label, &mut i, &names) {
+            *filter = Self::ALL[i];
+        }
+    }
+}
+
+/// Search and quick filters for the log table. Only lives as long as the game session.
+#[derive(Debug, Clone)]
+pub struct LogFilter {
+    pub query: String,
+    pub kills_only: bool,
+    pub failed_only: bool,
+    pub cm_only: bool,
+    pub dpsreport: StatusFilter,
+    pub wingman: StatusFilter,
+}
+
+impl LogFilter {
+    pub const fn new() -> Self {
+        Self {
+            query: String::new(),
+            kills_only: false,
+            failed_only: false,
+            cm_only: false,
+            dpsreport: StatusFilter::Any,
+            wingman: StatusFilter::Any,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty()
+            || self.kills_only
+            || self.failed_only
+            || self.cm_only
+            || self.dpsreport != StatusFilter::Any
+            || self.wingman != StatusFilter::Any
+    }
+
+    pub fn matches(&self, log: &Log) -> bool {
+        let wingman_failed = matches!(log.wingman, Step::Done(false));
+        if self.kills_only && log.success() != Some(true) {
+            return false;
+        }
+        if self.failed_only && !log.upload_failed() {
+            return false;
+        }
+        if self.cm_only && !log.is_cm() {
+            return false;
+        }
+        if !self.dpsreport.matches(&log.dpsreport, false)
+            || !self.wingman.matches(&log.wingman, wingman_failed)
+        {
+            return false;
+        }
+        self.query.is_empty() || log.matches_query(&self.query.to_lowercase())
+    }
+
+    pub fn render(&mut self, ui: &Ui) {
+        let width = ui.content_region_avail()[0] - ui.calc_text_size(e("Filters"))[0] - 20.0;
+        ui.set_next_item_width(width);
+        ui.input_text("##logsearch", &mut self.query)
+            .hint(e("Search boss, character or account"))
+            .build();
+        ui.same_line();
+        if ui.button(if self.is_active() {
+            e("Filters") + " *##logfilters"
+        } else {
+            e("Filters") + "##logfilters"
+        }) {
+            ui.open_popup("##logfilterpopup");
+        }
+        ui.popup("##logfilterpopup", || {
+            ui.checkbox(e("Kills only"), &mut self.kills_only);
+            ui.checkbox(e("Failed uploads only"), &mut self.failed_only);
+            ui.checkbox(e("CM only"), &mut self.cm_only);
+            StatusFilter::render(ui, e("dps.report##statusfilter"), &mut self.dpsreport);
+            StatusFilter::render(ui, e("Wingman##statusfilter"), &mut self.wingman);
+            if ui.button(e("Reset")) {
+                *self = Self::new();
+            }
+        });
+    }
+}
@@ -0,0 +1,61 @@
+use log_uploader_core::notifications::Notifications;
+use nexus::{alert::send_alert, imgui::Ui};
+
+use crate::util::{e, e_fill};
+
+pub enum Notification<'a> {
+    Kill { boss: &'a str, permalink: &'a str },
+    UploadFailed { target: &'a str, boss: &'a str },
+    RateLimited,
+    WatcherLost { path: &'a str },
+    Offline,
+    Online,
+}
+
+impl Notification<'_> {
+    fn enabled(&self, settings: &Notifications) -> bool {
+        match self {
+            Self::Kill { .. } => settings.kill,
+            Self::UploadFailed { .. } => settings.upload_failed,
+            Self::RateLimited => settings.rate_limited,
+            Self::WatcherLost { .. } => settings.watcher_lost,
+            Self::Offline | Self::Online => settings.connection,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Self::Kill { boss, permalink } => format!("{boss}: {permalink}"),
+            Self::UploadFailed { target, boss } => {
+                e_fill("Upload failed to {}: {}", &[target, boss])
+            }
+            Self::RateLimited => e("dps.report rate limit reached, retrying in 30 seconds"),
+            Self::WatcherLost { path } => e_fill("Stopped watching {}", &[path]),
+            Self::Offline => e("Upload servers unreachable, uploads wait for the connection"),
+            Self::Online => e("Back online, uploading queued logs"),
+        }
+    }
+
+    pub fn send(&self, settings: &Notifications) {
+        if self.enabled(settings) {
+            send_alert(self.message());
+        }
+    }
+}
+
+pub fn render(ui: &Ui, notifications: &mut Notifications) {
+    ui.checkbox(e("Kill uploaded to dps.report"), &mut notifications.kill);
+    ui.checkbox(e("Upload failed"), &mut notifications.upload_failed);
+    ui.checkbox(
+        e("Rate limited by dps.report"),
+        &mut notifications.rate_limited,
+    );
+    ui.checkbox(
+        e("Log folder no longer watched"),
+        &mut notifications.watcher_lost,
+    );
+    ui.checkbox(
+        e("Connection lost and restored"),
+        &mut notifications.connection,
+    );
+}
@@ -0,0 +1,149 @@
+use std::cell::Cell;
+
+use log_uploader_core::{arcdpslog::Log, settings::Settings, summary::collect_urls};
+use nexus::{
+    imgui::Ui,
+    quick_access::{add_quick_access, add_quick_access_context_menu, remove_quick_access},
+    render,
+};
+
+use crate::{
+    KB_IDENTIFIER, STATE,
+    assets::{
+        QUICKACCESS_ERROR, QUICKACCESS_HOVER, QUICKACCESS_IDLE, QUICKACCESS_PAUSED,
+        QUICKACCESS_UPLOADING,
+    },
+    settings,
+    util::e,
+};
+
+const QA_IDENTIFIER: &str = "QA_WINGMAN_UPLOADS";
+const QA_MENU_IDENTIFIER: &str = "QA_WINGMAN_UPLOADS_MENU";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueueState {
+    Idle,
+    Uploading,
+    Error,
+    Paused,
+    Offline,
+    Waiting,
+    Awaiting,
+}
+
+impl QueueState {
+    fn from_logs(logs: &[Log], paused: bool, offline: bool) -> Self {
+        if logs.iter().any(Log::upload_failed) {
+            Self::Error
+        } else if paused {
+            Self::Paused
+        } else if offline {
+            Self::Offline
+        } else if logs.iter().any(|l| l.waiting) {
+            Self::Waiting
+        } else if logs
+            .iter()
+            .any(|l| !l.is_finished() && !l.is_awaiting_approval())
+        {
+            Self::Uploading
+        } else if logs.iter().any(Log::is_awaiting_approval) {
+            Self::Awaiting
+        } else {
+            Self::Idle
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            Self::Idle => QUICKACCESS_IDLE,
+            Self::Uploading => QUICKACCESS_UPLOADING,
+            Self::Error | Self::Offline => QUICKACCESS_ERROR,
+            // Nothing is uploading, same as being paused
+            Self::Paused | Self::Waiting | Self::Awaiting => QUICKACCESS_PAUSED,
+        }
+    }
+
+    fn tooltip(self) -> String {
+        match self {
+            Self::Idle => e("Log Uploader"),
+            Self::Uploading => e("Log Uploader: uploading"),
+            Self::Error => e("Log Uploader: some uploads failed"),
+            Self::Paused => e("Log Uploader: paused"),
+            Self::Offline => e("Log Uploader: offline"),
+            Self::Waiting => e("Log Uploader: waiting for idle"),
+            Self::Awaiting => e("Log Uploader: logs awaiting approval"),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<QueueState>> = const { Cell::new(None) };
+}
+
+// Clicking the shortcut triggers the keybind, so it toggles the window just like the keybind does
+fn add(state: QueueState) {
+    add_quick_access(
+        QA_IDENTIFIER,
+        state.icon(),
+        QUICKACCESS_HOVER,
+        KB_IDENTIFIER,
+        state.tooltip(),
+    );
+}
+
+pub fn load() {
+    add(QueueState::Idle);
+    CURRENT.set(Some(QueueState::Idle));
+    add_quick_access_context_menu(
+        QA_MENU_IDENTIFIER,
+        Some(QA_IDENTIFIER),
+        render!(render_context_menu),
+    )
+    .revert_on_unload();
+}
+
+pub fn unload() {
+    remove_quick_access(QA_IDENTIFIER);
+}
+
+/// Nexus can't change the icon of a shortcut, so it gets replaced whenever the state changes
+pub fn update(logs: &[Log], paused: bool, offline: bool) {
+    let state = QueueState::from_logs(logs, paused, offline);
+    if CURRENT.get() != Some(state) {
+        remove_quick_access(QA_IDENTIFIER);
+        add(state);
+        CURRENT.set(Some(state));
+    }
+}
+
+fn render_context_menu(ui: &Ui) {
+    let mut uploader = STATE.uploader.lock().unwrap();
+    let Some(uploader) = uploader.as_mut() else {
+        return;
+    };
+    let logs = &mut uploader.logs;
+    let mut settings = Settings::get_mut();
+    let mut paused = settings.paused;
+    if ui.checkbox(e("Pause uploads"), &mut paused) {
+        settings::set_paused(&mut settings, paused);
+    }
+    if ui.button(e("Copy dps.report urls")) {
+        let urls = collect_urls(logs, &settings);
+        if !urls.is_empty() {
+            ui.set_clipboard_text(urls);
+        }
+    }
+    let failed = logs.iter().filter(|l| l.upload_failed()).count();
+    if failed > 0 && ui.button(format!("{} ({failed})", e("Retry failed"))) {
+        logs.iter_mut().for_each(Log::retry_failed);
+    }
+    if logs
+        .iter()
+        .any(|l| l.is_awaiting_approval() && l.success() == Some(true))
+        && ui.button(e("Upload all kills"))
+    {
+        logs.iter_mut()
+            .filter(|l| l.is_awaiting_approval() && l.success() == Some(true))
+            .for_each(Log::approve);
+    }
+}
@@ -1,170 +1,58 @@
 use std::{
     cell::{Cell, RefCell},
-    fs::{File, create_dir_all},
     path::{Path, PathBuf},
-    sync::{Mutex, MutexGuard},
 };
 
-use dirs_next::document_dir;
+use log_uploader_core::{
+    dpsreport::{CategoryOptions, DpsReportOptions, Generator, UploadOptions},
+    http::{self, Http, ProxyMode},
+    profile::NamedProfile,
+    schedule::{Defer, Schedule},
+    settings::Settings,
+    summary::Category,
+    table::TimeFormat,
+    theme::Theme,
+};
 use nexus::{
-    imgui::{ButtonFlags, ItemFlag, StyleColor, StyleVar, Ui},
+    imgui::{StyleColor, StyleVar, Ui},
     paths::get_addon_dir,
 };
-use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::RED,
-    util::{UiExt, e},
+    notifications,
+    theme::ThemeExt,
+    util::{UiExt, e, e_fill},
 };
 
-fn default_true() -> bool {
-    true
-}
-
-fn default_copyformat() -> String {
-    String::from("@1")
+pub(crate) fn config_path() -> PathBuf {
+    get_addon_dir("wingman-uploader")
+        .expect("Addon dir to exist")
+        .join("settings.json")
 }
 
-// serde defaults only for the case, the file exists, but doesnt contain all the fields
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Settings {
-    pub logpath: String,
-    pub dpsreport_token: String,
-    #[serde(default = "default_copyformat")]
-    pub dpsreport_copyformat: String,
-    #[serde(default)]
-    pub show_window: bool,
-    #[serde(default)]
-    pub rev_log_order: bool,
-    #[serde(default = "default_true")]
-    pub copy_success: bool,
-    #[serde(default = "default_true")]
-    pub copy_failure: bool,
-    #[serde(default = "default_true")]
-    pub enable_dpsreport: bool,
-    #[serde(default = "default_true")]
-    pub enable_wingman: bool,
-    #[serde(default)]
-    pub filter_dpsreport: Vec<u16>,
-    #[serde(default)]
-    pub filter_wingman: Vec<u16>,
-    #[serde(default)]
-    pub hide_hotfix_notification_20241114: bool,
-    #[serde(default)]
-    pub hotfix_20250512_executed: bool,
+pub(crate) fn history_path() -> PathBuf {
+    get_addon_dir("wingman-uploader")
+        .expect("Addon dir to exist")
+        .join("history.jsonl")
 }
 
-impl Settings {
-    const fn default() -> Self {
-        Self {
-            // Cannot use default_dir() because it's not consat
-            logpath: String::new(),
-            dpsreport_token: String::new(),
-            // Cannot use default_copyformat() because it's not const
-            dpsreport_copyformat: String::new(),
-            show_window: true,
-            rev_log_order: false,
-            copy_success: true,
-            copy_failure: true,
-            enable_dpsreport: true,
-            enable_wingman: true,
-            filter_wingman: Vec::new(),
-            filter_dpsreport: Vec::new(),
-            hide_hotfix_notification_20241114: false,
-            hotfix_20250512_executed: false,
-        }
-    }
-
-    pub fn init(&mut self) {
-        self.logpath = Self::default_dir().display().to_string();
-        self.dpsreport_copyformat = default_copyformat();
-    }
-
-    pub fn get() -> MutexGuard<'static, Self> {
-        SETTINGS.lock().unwrap()
-    }
-    pub fn get_mut() -> MutexGuard<'static, Self> {
-        SETTINGS.lock().unwrap()
-    }
-
-    pub fn default_dir() -> PathBuf {
-        let mut base = document_dir().unwrap_or_default();
-        base.push("Guild Wars 2");
-        base.push("addons");
-        base.push("arcdps");
-        base.push("arcdps.cbtlogs");
-        base
-    }
-
-    // Default was an empty string if config file did not exist yet
-    // If the file existed (even if empty) it worked correctly
-    fn check_hotfix20250512(&self) -> bool {
-        self.dpsreport_copyformat.is_empty()
-    }
-
-    pub fn fix_hotfix20250512(&mut self) {
-        // Only do this once so if someone actually uses an empty copyformat we wont overwrite it
-        // next restart
-        if !self.hotfix_20250512_executed && self.check_hotfix20250512() {
-            log::info!("Empty copyformat detected, setting default (Hotfix 20250512)");
-            self.dpsreport_copyformat = default_copyformat();
-        }
-        // Always set this to true so we don't run this again
-        self.hotfix_20250512_executed = true;
-    }
-
-    pub fn check_hotfix20241114(&self) -> bool {
-        self.logpath.ends_with("arcdps.cbtlog")
-    }
-
-    pub fn fix_hotfix20241114(&mut self) {
-        self.logpath = Settings::default_dir().to_string_lossy().to_string();
-    }
-
-    pub fn enable_dpsreport(&self) -> bool {
-        self.enable_dpsreport
-    }
-
-    pub fn logpath(&self) -> &str {
-        &self.logpath
-    }
-
-    pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<()> {
-        let path = path.as_ref();
-        if path.exists() {
-            let contents = std::fs::read_to_string(path)?;
-            let mut settings: Self = serde_json::from_str(&contents)?;
-            settings.fix_hotfix20250512();
-            *SETTINGS.lock().unwrap() = settings;
-        } else {
-            // Need to set here because it's not const
-            let mut settings = SETTINGS.lock().unwrap();
-            settings.init();
-        }
-        Ok(())
-    }
-
-    pub fn store(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
-        let path = path.as_ref();
-        let prefix = path.parent().unwrap();
-        create_dir_all(prefix)?;
-        let mut file = File::options()
-            .write(true)
-            .append(false)
-            .create(true)
-            .truncate(true)
-            .open(path)?;
-        serde_json::to_writer_pretty(&mut file, self)?;
-        Ok(())
+/// Like pausing, switching profiles is stored right away
+pub(crate) fn set_profile(settings: &mut Settings, name: String) {
+    log::info!("Switching to profile {name:?}");
+    settings.active_profile = name;
+    if let Err(e) = settings.store(config_path()) {
+        log::error!("Failed to store settings: {e}");
     }
 }
 
-pub(crate) fn config_path() -> PathBuf {
-    get_addon_dir("wingman-uploader")
-        .expect("Addon dir to exist")
-        .join("settings.json")
+/// Pausing is stored right away so it survives a crash or restart without pressing save
+pub(crate) fn set_paused(settings: &mut Settings, paused: bool) {
+    log::info!("{} uploads", if paused { "Pausing" } else { "Resuming" });
+    settings.paused = paused;
+    if let Err(e) = settings.store(config_path()) {
+        log::error!("Failed to store settings: {e}");
+    }
 }
-static SETTINGS: Mutex<Settings> = Mutex::new(Settings::default());
 
 fn validate_path(path: &str) -> bool {
     let path = Path::new(path);
@@ -182,16 +70,18 @@ pub fn render(ui: &Ui) {
         static FILTER_DPSREPORT: RefCell<Vec<u16>> = const { RefCell::new(Vec::new()) };
         static EDIT_TOKEN: Cell<bool> = const { Cell::new(false) };
         static EDIT_COPYFORMAT: Cell<bool> = const { Cell::new(false) };
+        static PROFILE: RefCell<String> = const { RefCell::new(String::new()) };
         static INITIALIZED: Cell<bool> = const { Cell::new(false) };
     }
 
     if !INITIALIZED.get() {
-        let settings = SETTINGS.lock().unwrap();
+        let settings = Settings::get();
         LOGPATH.set(settings.logpath.clone());
-        DPSREPORT_TOKEN.set(settings.dpsreport_token.clone());
-        DPSREPORT_COPYFORMAT.set(settings.dpsreport_copyformat.clone());
-        FILTER_WINGMAN.set(settings.filter_wingman.clone());
-        FILTER_DPSREPORT.set(settings.filter_dpsreport.clone());
+        DPSREPORT_TOKEN.set(settings.active().dpsreport_token.clone());
+        DPSREPORT_COPYFORMAT.set(settings.active().dpsreport_copyformat.clone());
+        FILTER_WINGMAN.set(settings.active().filter_wingman.clone());
+        FILTER_DPSREPORT.set(settings.active().filter_dpsreport.clone());
+        PROFILE.set(settings.active_profile.clone());
         INITIALIZED.set(true);
     }
 
@@ -202,7 +92,7 @@ pub fn render(ui: &Ui) {
         None
     };
     if ui.button(e("Save") + "##saveconfig") && valid {
-        let mut settings = SETTINGS.lock().unwrap();
+        let mut settings = Settings::get_mut();
         log::trace!("Storing config");
         if let Err(e) = settings.store(config_path()) {
             log::error!("Failed to store settings: {e}");
@@ -212,14 +102,15 @@ pub fn render(ui: &Ui) {
         stylevar.end();
     }
 
+    let theme = Settings::get().theme.clone();
     let color = if !PATH_VALID.get() {
-        Some(ui.push_style_color(StyleColor::FrameBg, RED))
+        Some(ui.push_style_color(StyleColor::FrameBg, theme.failure()))
     } else {
         None
     };
     // logpath
     LOGPATH.with_borrow_mut(|lp| {
-        ui.input_text("Logpath", lp)
+        ui.input_text(e("Logpath"), lp)
             .read_only(!PATH_EDIT.get())
             .build()
     });
@@ -243,7 +134,7 @@ pub fn render(ui: &Ui) {
                     // we are done editing
                     PATH_EDIT.set(false);
 
-                    let mut settings = SETTINGS.lock().unwrap();
+                    let mut settings = Settings::get_mut();
                     settings.logpath = lp.clone();
                 }
             });
@@ -251,17 +142,30 @@ pub fn render(ui: &Ui) {
             PATH_EDIT.set(true);
         }
         if !PATH_VALID.get() {
-            ui.attention_marker(|| ui.text_colored(RED, e("Invalid path")));
+            ui.attention_marker(|| {
+                ui.text_colored(theme.failure(), theme.label(false, e("Invalid path")))
+            });
         }
     }
+    let mut settings = Settings::get_mut();
+    ui.separator();
+    render_profiles(ui, &mut settings);
+    // The edit buffers belong to the profile they were started in
+    if PROFILE.with_borrow(|p| *p != settings.active_profile) {
+        PROFILE.set(settings.active_profile.clone());
+        DPSREPORT_COPYFORMAT.set(settings.active().dpsreport_copyformat.clone());
+        EDIT_TOKEN.set(false);
+        EDIT_COPYFORMAT.set(false);
+    }
+    let logpath = settings.logpath.clone();
+    let profile = settings.active_mut();
     // dpsreport
-    let mut settings = SETTINGS.lock().unwrap();
     DPSREPORT_TOKEN.with_borrow_mut(|token| {
-        if !EDIT_TOKEN.get() && token.as_str() != settings.dpsreport_token.as_str() {
+        if !EDIT_TOKEN.get() && token.as_str() != profile.dpsreport_token.as_str() {
             // we are not editing but token changed
-            // can only happen if dps report response was successful
+            // can only happen if dps report response was successful or the profile changed
             // Update local input token
-            *token = settings.dpsreport_token.clone();
+            *token = profile.dpsreport_token.clone();
         }
         ui.input_text(e("dps.report Token"), token)
             .read_only(!EDIT_TOKEN.get())
@@ -278,7 +182,7 @@ pub fn render(ui: &Ui) {
         if EDIT_TOKEN.get() {
             // Set button was clicked
             DPSREPORT_TOKEN.with_borrow(|token| {
-                settings.dpsreport_token = token.clone();
+                profile.dpsreport_token = token.clone();
             });
         }
         EDIT_TOKEN.set(!EDIT_TOKEN.get())
@@ -291,13 +195,13 @@ pub fn render(ui: &Ui) {
     });
     ui.help_marker(|| {
         ui.tooltip(|| {
-            ui.text(
+            ui.text(e(
                 "You can configure the format that your dps.report url strings are copied as using the following parameters:",
-            );
-            ui.text("@1 - dps.report url");
-            ui.text("@2 - boss name and CM status");
-            ui.text("@3 - boss id");
-            ui.text("@4 - encounter success/fail");
+            ));
+            ui.text(e("@1 - dps.report url"));
+            ui.text(e("@2 - boss name and CM status"));
+            ui.text(e("@3 - boss id"));
+            ui.text(e("@4 - encounter success/fail"));
         })
     });
     ui.same_line();
@@ -310,51 +214,423 @@ pub fn render(ui: &Ui) {
         if EDIT_COPYFORMAT.get() {
             // Set button was clicked
             DPSREPORT_COPYFORMAT.with_borrow(|copyformat| {
-                settings.dpsreport_copyformat = copyformat.clone();
+                profile.dpsreport_copyformat = copyformat.clone();
             });
         }
         EDIT_COPYFORMAT.set(!EDIT_COPYFORMAT.get())
     }
     ui.checkbox(e("Display new logs at top"), &mut settings.rev_log_order);
+    render_columns(ui, &mut settings);
+    settings.theme.render(ui);
+    let mut max_logs = settings.max_logs as i32;
+    if ui.input_int(e("Max logs in list"), &mut max_logs).build() {
+        settings.max_logs = max_logs.max(1) as usize;
+    }
+    ui.help_marker(|| {
+        ui.tooltip_text(e(
+            "Finished logs are removed from the list once there are more. They are kept in the statistics history.",
+        ))
+    });
+    let mut max_age = settings.max_log_age_hours as i32;
+    if ui
+        .input_int(e("Remove finished logs after (hours)"), &mut max_age)
+        .build()
+    {
+        settings.max_log_age_hours = max_age.max(0) as u64;
+    }
+    ui.help_marker(|| ui.tooltip_text(e("0 keeps them until the game is closed")));
+    let mut gap = settings.session_gap_minutes as i32;
+    if ui.input_int(e("Session gap (minutes)"), &mut gap).build() {
+        settings.session_gap_minutes = gap.max(1) as u64;
+    }
+    ui.help_marker(|| {
+        ui.tooltip_text(e(
+            "A new session starts if no log was created for this many minutes",
+        ))
+    });
+
+    ui.separator();
+    ui.text(e("Notifications:"));
+    notifications::render(ui, &mut settings.notifications);
+
+    ui.separator();
+    render_schedule(ui, &mut settings.schedule);
+    ui.checkbox(e("Approve uploads manually"), &mut settings.approval_mode);
+    ui.help_marker(|| {
+        ui.tooltip_text(e(
+            "New logs are parsed but only uploaded once you pick them in the window",
+        ))
+    });
 
     ui.separator();
-    ui.checkbox(e("Enable dps.report"), &mut settings.enable_dpsreport);
-    ui.text("Don't upload logs to dps.report with the following boss ids:");
+    render_network(ui, &mut settings.http, &theme);
+
+    ui.separator();
+    ui.checkbox(
+        e("Enable dps.report"),
+        &mut settings.active_mut().enable_dpsreport,
+    );
+    ui.text(e(
+        "Don't upload logs to dps.report with the following boss ids:",
+    ));
     if ui.help_marker(|| {
         ui.tooltip(|| {
-            ui.text(
+            ui.text(e(
                 "You can check your log folder for the boss ids. It is the number in parentheses.",
-            );
-            ui.text("For example: Gorseval the Multifarious (15429)");
-            ui.text("The boss id would be 15429.");
-            ui.text("Click to open log folder.");
+            ));
+            ui.text(e("For example: Gorseval the Multifarious (15429)"));
+            ui.text(e("The boss id would be 15429."));
+            ui.text(e("Click to open log folder."));
         })
     }) {
-        if let Err(e) = open::that_detached(&settings.logpath) {
+        if let Err(e) = open::that_detached(&logpath) {
             log::error!("Failed to open log folder: {e}");
         }
     }
-    render_dpsreport_filter(ui, &mut settings.filter_dpsreport);
+    render_dpsreport_filter(ui, &mut settings.active_mut().filter_dpsreport);
+    render_dpsreport_options(ui, &mut settings.dpsreport_options);
+    ui.checkbox(
+        e("Fetch Elite Insights details after uploading"),
+        &mut settings.fetch_ei_json,
+    );
+    ui.help_marker(|| {
+        ui.tooltip_text(e(
+            "Downloads the report data for the detail panel (right click a log). Without this they are fetched when the panel is opened.",
+        ))
+    });
     ui.separator();
     // wingman
-    ui.checkbox(e("Enable Wingman"), &mut settings.enable_wingman);
-    ui.text("Don't upload logs to Wingman with the following boss ids:");
+    ui.checkbox(
+        e("Enable Wingman"),
+        &mut settings.active_mut().enable_wingman,
+    );
+    ui.text(e(
+        "Don't upload logs to Wingman with the following boss ids:",
+    ));
     if ui.help_marker(|| {
         ui.tooltip(|| {
-            ui.text(
+            ui.text(e(
                 "You can check your log folder for the boss ids. It is the number in parentheses.",
-            );
-            ui.text("For example: Large Kitty Golem (19676)");
-            ui.text("The boss id would be 19676.");
-            ui.text("WvW logs are skipped by default. (ID: 1)");
-            ui.text("Click to open log folder.");
+            ));
+            ui.text(e("For example: Large Kitty Golem (19676)"));
+            ui.text(e("The boss id would be 19676."));
+            ui.text(e("WvW logs are skipped by default. (ID: 1)"));
+            ui.text(e("Click to open log folder."));
         })
     }) {
-        if let Err(e) = open::that_detached(&settings.logpath) {
+        if let Err(e) = open::that_detached(&logpath) {
             log::error!("Failed to open log folder: {e}");
         }
     }
-    render_wingman_filter(ui, &mut settings.filter_wingman);
+    render_wingman_filter(ui, &mut settings.active_mut().filter_wingman);
+}
+
+/// Switching, creating and deleting profiles and the rules for picking them automatically
+fn render_profiles(ui: &Ui, settings: &mut Settings) {
+    thread_local! {
+        static ACCOUNT: RefCell<String> = const { RefCell::new(String::new()) };
+        static CATEGORY: Cell<usize> = const { Cell::new(0) };
+    }
+    let mut names = vec![e("Default")];
+    names.extend(settings.profiles.iter().map(|p| p.name.clone()));
+    let active = settings
+        .profiles
+        .iter()
+        .position(|p| p.name == settings.active_profile);
+    let mut selected = active.map_or(0, |i| i + 1);
+    ui.set_next_item_width(200.0);
+    if ui.combo_simple_string(e("Profile##profileselect"), &mut selected, &names) {
+        settings.active_profile = match selected {
+            0 => String::new(),
+            n => settings.profiles[n - 1].name.clone(),
+        };
+    }
+    ui.same_line();
+    if ui.button(e("New##newprofile")) {
+        let name = (1..)
+            .map(|i| e_fill("Profile {}", &[&i]))
+            .find(|n| !settings.profiles.iter().any(|p| p.name == *n))
+            .expect("a free profile name");
+        let profile = NamedProfile::new(name.clone(), settings.active().clone());
+        settings.profiles.push(profile);
+        settings.active_profile = name;
+        return;
+    }
+    ui.help_marker(|| {
+        ui.tooltip_text(e(
+            "A profile holds the dps.report token, the enabled targets, the filters and the copy format. New profiles start as a copy of the selected one.",
+        ))
+    });
+    ui.checkbox(e("Pick profile automatically"), &mut settings.auto_profile);
+    ui.help_marker(|| {
+        ui.tooltip_text(e(
+            "Logs use the first profile whose accounts or categories match, the selected profile otherwise",
+        ))
+    });
+    let Some(i) = active else {
+        return;
+    };
+    let mut name = settings.profiles[i].name.clone();
+    ui.set_next_item_width(200.0);
+    let renamed = ui.input_text(e("Name##profilename"), &mut name).build();
+    if renamed && !name.is_empty() && !settings.profiles.iter().any(|p| p.name == name) {
+        settings.profiles[i].name = name.clone();
+        settings.active_profile = name;
+    }
+    ui.same_line();
+    if ui.button(e("Delete##deleteprofile")) {
+        settings.profiles.remove(i);
+        settings.active_profile.clear();
+        return;
+    }
+    if !settings.auto_profile {
+        return;
+    }
+    let profile = &mut settings.profiles[i];
+    ui.text(e("Use for logs recorded by these accounts:"));
+    let mut to_remove = None;
+    for (i, account) in profile.accounts.iter().enumerate() {
+        ui.align_text_to_frame_padding();
+        ui.text(account);
+        ui.same_line();
+        if ui.button(e("remove") + &format!("##profileaccountremove{i}")) {
+            to_remove = Some(i);
+        }
+    }
+    if let Some(i) = to_remove {
+        profile.accounts.remove(i);
+    }
+    ACCOUNT.with_borrow_mut(|account| {
+        ui.set_next_item_width(250.0);
+        ui.input_text("##profileaccount", account)
+            .hint("Account.1234")
+            .build();
+        ui.same_line();
+        if ui.button(e("Add##profileaccountadd")) && !account.trim().is_empty() {
+            profile.accounts.push(account.trim().to_string());
+            account.clear();
+        }
+    });
+    ui.text(e("Use for logs of these categories:"));
+    let mut to_remove = None;
+    for (i, category) in profile.categories.iter().enumerate() {
+        ui.align_text_to_frame_padding();
+        ui.text(category.to_string());
+        ui.same_line();
+        if ui.button(e("remove") + &format!("##profilecategoryremove{i}")) {
+            to_remove = Some(i);
+        }
+    }
+    if let Some(i) = to_remove {
+        profile.categories.remove(i);
+    }
+    let free: Vec<_> = Category::ALL
+        .into_iter()
+        .filter(|c| !profile.categories.contains(c))
+        .collect();
+    if free.is_empty() {
+        return;
+    }
+    let mut category = CATEGORY.get().min(free.len() - 1);
+    let names: Vec<_> = free.iter().map(Category::to_string).collect();
+    ui.set_next_item_width(250.0);
+    ui.combo_simple_string("##profilecategory", &mut category, &names);
+    CATEGORY.set(category);
+    ui.same_line();
+    if ui.button(e("Add##profilecategoryadd")) {
+        profile.categories.push(free[category]);
+    }
+}
+
+fn render_columns(ui: &Ui, settings: &mut Settings) {
+    ui.text(e("Columns:"));
+    let columns = &mut settings.columns;
+    ui.checkbox(e("Start##columnstart"), &mut columns.start);
+    ui.same_line();
+    ui.checkbox(e("Duration##columnduration"), &mut columns.duration);
+    ui.same_line();
+    ui.checkbox(e("Created##columncreated"), &mut columns.created);
+    ui.help_marker(|| ui.tooltip_text(e("Modification time of the log file")));
+    ui.checkbox(e("dps.report##columndpsreport"), &mut columns.dpsreport);
+    ui.same_line();
+    ui.checkbox(e("Wingman##columnwingman"), &mut columns.wingman);
+    ui.same_line();
+    ui.checkbox(
+        e("Open in Folder##columnfolder"),
+        &mut columns.open_in_folder,
+    );
+
+    let mut format = TimeFormat::ALL
+        .iter()
+        .position(|f| *f == settings.time_format)
+        .unwrap_or_default();
+    let names = TimeFormat::ALL.map(TimeFormat::name);
+    if ui.combo_simple_string(e("Time format"), &mut format, &names) {
+        settings.time_format = TimeFormat::ALL[format];
+    }
+    ui.same_line();
+    ui.checkbox(e("Include date"), &mut settings.time_include_date);
+}
+
+fn render_schedule(ui: &Ui, schedule: &mut Schedule) {
+    ui.text(e("Scheduling:"));
+    let mut defer = Defer::ALL
+        .iter()
+        .position(|d| *d == schedule.defer)
+        .unwrap_or_default();
+    let names = [
+        e("Upload right away"),
+        e("Wait until idle"),
+        e("Wait until out of combat"),
+    ];
+    if ui.combo_simple_string(e("Start uploads"), &mut defer, &names) {
+        schedule.defer = Defer::ALL[defer];
+    }
+    ui.help_marker(|| {
+        ui.tooltip_text(e(
+            "Uploading during a fight can cause stutters. Logs are still parsed right away.",
+        ))
+    });
+    if schedule.defer == Defer::Idle {
+        let mut idle = schedule.idle_secs as i32;
+        if ui
+            .input_int(e("Idle after no new log for (seconds)"), &mut idle)
+            .build()
+        {
+            schedule.idle_secs = idle.max(0) as u64;
+        }
+    }
+    if schedule.defer != Defer::Never {
+        ui.checkbox(e("Hold back dps.report"), &mut schedule.dpsreport);
+        ui.same_line();
+        ui.checkbox(e("Hold back Wingman"), &mut schedule.wingman);
+    }
+}
+
+fn render_network(ui: &Ui, http: &mut Http, theme: &Theme) {
+    thread_local! {
+        // Building the client reads the certificate file, so only check after changes
+        static CHECKED: RefCell<Option<(Http, Option<String>)>> = const { RefCell::new(None) };
+    }
+    ui.text(e("Network:"));
+    let timeouts = [
+        (e("Connect timeout (seconds)"), &mut http.connect_timeout),
+        (e("Read timeout (seconds)"), &mut http.read_timeout),
+        (e("Write timeout (seconds)"), &mut http.write_timeout),
+    ];
+    for (label, timeout) in timeouts {
+        let mut value = *timeout as i32;
+        if ui.input_int(label, &mut value).build() {
+            *timeout = value.max(0) as u64;
+        }
+    }
+    ui.help_marker(|| ui.tooltip_text(e("0 waits forever")));
+
+    let mut mode = ProxyMode::ALL
+        .iter()
+        .position(|m| *m == http.proxy)
+        .unwrap_or_default();
+    let names = [e("No proxy"), e("System proxy"), e("Manual proxy")];
+    if ui.combo_simple_string(e("Proxy"), &mut mode, &names) {
+        http.proxy = ProxyMode::ALL[mode];
+    }
+    if http.proxy == ProxyMode::Manual {
+        ui.input_text(e("Proxy url"), &mut http.proxy_url).build();
+        ui.help_marker(|| {
+            ui.tooltip_text(e(
+                "For example http://proxy:8080 or socks5://localhost:1080",
+            ))
+        });
+    }
+    ui.input_text(e("Additional root certificates"), &mut http.ca_file)
+        .build();
+    ui.help_marker(|| {
+        ui.tooltip_text(e(
+            "Path to a PEM file, for proxies which inspect https traffic",
+        ))
+    });
+
+    let error = CHECKED.with_borrow_mut(|checked| match checked {
+        Some((config, error)) if config == http => error.clone(),
+        _ => {
+            let error = http::build(http).err().map(|e| format!("{e:#}"));
+            *checked = Some((http.clone(), error.clone()));
+            error
+        }
+    });
+    if let Some(error) = error {
+        ui.text_colored(theme.failure(), theme.label(false, error));
+    }
+}
+
+/// Checkboxes and generator combo, `id` keeps the widgets of different rows apart
+fn render_upload_options(ui: &Ui, options: &mut UploadOptions, id: &str) {
+    ui.checkbox(
+        format!("{}##anonymous{id}", e("Anonymous")),
+        &mut options.anonymous,
+    );
+    ui.same_line();
+    ui.checkbox(
+        format!("{}##detailedwvw{id}", e("Detailed WvW")),
+        &mut options.detailed_wvw,
+    );
+    ui.same_line();
+    let mut generator = Generator::ALL
+        .iter()
+        .position(|g| *g == options.generator)
+        .unwrap_or_default();
+    let names = Generator::ALL.map(Generator::name);
+    ui.set_next_item_width(150.0);
+    if ui.combo_simple_string(
+        format!("{}##generator{id}", e("Generator")),
+        &mut generator,
+        &names,
+    ) {
+        options.generator = Generator::ALL[generator];
+    }
+}
+
+fn render_dpsreport_options(ui: &Ui, options: &mut DpsReportOptions) {
+    thread_local! {
+        static CATEGORY: Cell<usize> = const { Cell::new(0) };
+    }
+    ui.text(e("dps.report upload options:"));
+    render_upload_options(ui, &mut options.default, "default");
+    ui.text(e("Overrides per category:"));
+    let mut to_remove = None;
+    for (i, o) in options.overrides.iter_mut().enumerate() {
+        ui.align_text_to_frame_padding();
+        ui.text(o.category.to_string());
+        ui.same_line();
+        render_upload_options(ui, &mut o.options, &format!("override{i}"));
+        ui.same_line();
+        if ui.button(e("remove") + &format!("##optionsremove{i}")) {
+            to_remove = Some(i);
+        }
+    }
+    if let Some(i) = to_remove {
+        options.overrides.remove(i);
+    }
+    // Every category can only be overridden once
+    let free: Vec<_> = Category::ALL
+        .into_iter()
+        .filter(|c| !options.overrides.iter().any(|o| o.category == *c))
+        .collect();
+    if free.is_empty() {
+        return;
+    }
+    let mut category = CATEGORY.get().min(free.len() - 1);
+    let names: Vec<_> = free.iter().map(Category::to_string).collect();
+    ui.set_next_item_width(250.0);
+    ui.combo_simple_string("##optionscategory", &mut category, &names);
+    CATEGORY.set(category);
+    ui.same_line();
+    if ui.button(e("Add##optionsoverride")) {
+        options.overrides.push(CategoryOptions {
+            category: free[category],
+            options: options.default,
+        });
+    }
 }
 
 fn render_dpsreport_filter(ui: &Ui, filter: &mut Vec<u16>) {
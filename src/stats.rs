@@ -0,0 +1,212 @@
+use std::cell::Cell;
+
+use chrono::{DateTime, Local};
+use log_uploader_core::{
+    history, session::Session, summary, summary::format_duration, sync::SyncState,
+    uploader::Uploader,
+};
+use nexus::imgui::{TableColumnFlags, TableColumnSetup, TableFlags, Ui, Window};
+use revtc::bossdata::BossId;
+
+use crate::util::{e, e_fill, e_or};
+
+#[derive(Debug, Clone)]
+pub struct BossStats {
+    pub boss_id: u16,
+    pub boss: String,
+    pub attempts: usize,
+    pub kills: usize,
+    /// Unix timestamp of the first kill
+    pub first_kill: Option<i64>,
+    /// Duration of the fastest kill in seconds
+    pub fastest_kill: Option<u64>,
+    pub cm: usize,
+    pub lcm: usize,
+}
+
+impl BossStats {
+    fn new(entry: &history::Entry) -> Self {
+        Self {
+            boss_id: entry.boss_id,
+            boss: BossId::from_header_id(entry.boss_id).to_string(),
+            attempts: 0,
+            kills: 0,
+            first_kill: None,
+            fastest_kill: None,
+            cm: 0,
+            lcm: 0,
+        }
+    }
+
+    fn add(&mut self, entry: &history::Entry) {
+        self.attempts += 1;
+        if entry.is_legendary_cm {
+            self.lcm += 1;
+        } else if entry.is_cm {
+            self.cm += 1;
+        }
+        if !entry.success {
+            return;
+        }
+        self.kills += 1;
+        self.first_kill = Some(self.first_kill.map_or(entry.time, |t| t.min(entry.time)));
+        self.fastest_kill = Some(
+            self.fastest_kill
+                .map_or(entry.duration, |d| d.min(entry.duration)),
+        );
+    }
+
+    pub fn wipes(&self) -> usize {
+        self.attempts - self.kills
+    }
+
+    pub fn kill_ratio(&self) -> f32 {
+        self.kills as f32 / self.attempts.max(1) as f32
+    }
+}
+
+/// Aggregates the entries per boss, sorted in clear order
+pub fn aggregate<'a>(entries: impl IntoIterator<Item = &'a history::Entry>) -> Vec<BossStats> {
+    let mut stats: Vec<BossStats> = vec![];
+    for entry in entries {
+        // Bosses with multiple trigger ids share the same name. Older synced entries carry the
+        // dps.report name, so the name is taken from the id.
+        let boss = BossId::from_header_id(entry.boss_id).to_string();
+        let i = match stats.iter().position(|s| s.boss == boss) {
+            Some(i) => i,
+            None => {
+                stats.push(BossStats::new(entry));
+                stats.len() - 1
+            }
+        };
+        stats[i].add(entry);
+    }
+    stats.sort_by_key(|s| summary::category(s.boss_id));
+    stats
+}
+
+fn format_timestamp(ts: i64) -> String {
+    DateTime::from_timestamp(ts, 0)
+        .map(|dt| {
+            dt.with_timezone(&Local)
+                .format(&e_or("DATE_TIME_FORMAT", "%F %R"))
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Button to import the uploads of the token, with the result of the last sync next to it.
+/// Returns whether it was clicked.
+fn render_sync(ui: &Ui, uploader: &Uploader) -> bool {
+    let clicked = if uploader.sync == SyncState::Running {
+        ui.text_disabled(e("Syncing with dps.report..."));
+        false
+    } else {
+        ui.button(e("Sync with dps.report"))
+    };
+    if ui.is_item_hovered() {
+        ui.tooltip_text(e(
+            "Imports all uploads of your dps.report token, also from other computers and tools. Logs that were already uploaded are not uploaded again.",
+        ));
+    }
+    match &uploader.sync {
+        SyncState::Done { uploads, imported } => {
+            ui.same_line();
+            ui.text(e_fill("{} uploads, {} new", &[uploads, imported]));
+        }
+        SyncState::Failed(err) => {
+            ui.same_line();
+            ui.text(format!("{} {err}", e("Sync failed:")));
+        }
+        SyncState::Idle | SyncState::Running => {}
+    }
+    clicked
+}
+
+/// Returns whether a sync with dps.report was requested. The caller starts it, the settings it
+/// needs are locked while rendering.
+pub fn render(ui: &Ui, opened: &mut bool, uploader: &Uploader, current: Option<&Session>) -> bool {
+    thread_local! {
+        static SHOW_HISTORY: Cell<bool> = const { Cell::new(false) };
+    }
+    let Some(_w) = Window::new(e("Log Uploader Statistics"))
+        .opened(opened)
+        .collapsible(false)
+        .begin(ui)
+    else {
+        return false;
+    };
+
+    let mut show_history = SHOW_HISTORY.get();
+    if ui.radio_button_bool(e("Current session"), !show_history) {
+        show_history = false;
+    }
+    ui.same_line();
+    if ui.radio_button_bool(e("History"), show_history) {
+        show_history = true;
+    }
+    SHOW_HISTORY.set(show_history);
+    let sync = show_history && render_sync(ui, uploader);
+
+    let stats = if show_history {
+        aggregate(history::get().iter())
+    } else {
+        let logs = current
+            .map(|s| &uploader.logs[s.logs.clone()])
+            .unwrap_or_default();
+        let entries: Vec<_> = logs.iter().filter_map(history::Entry::from_log).collect();
+        aggregate(&entries)
+    };
+    if stats.is_empty() {
+        ui.text(e("No finished logs yet."));
+    } else {
+        render_table(ui, stats);
+    }
+    sync
+}
+
+fn render_table(ui: &Ui, stats: Vec<BossStats>) {
+    let columns = [
+        e("Boss"),
+        e("Attempts"),
+        e("Kills"),
+        e("Wipes"),
+        e("Kill %"),
+        e("First kill"),
+        e("Fastest kill"),
+        e("CM"),
+        e("LCM"),
+    ];
+    let Some(_t) = ui.begin_table_header_with_flags(
+        "Statistics",
+        columns.map(|name| TableColumnSetup {
+            name,
+            flags: TableColumnFlags::WIDTH_STRETCH,
+            init_width_or_weight: 0.0,
+            user_id: Default::default(),
+        }),
+        TableFlags::BORDERS_OUTER | TableFlags::BORDERS_INNER_V | TableFlags::ROW_BG,
+    ) else {
+        return;
+    };
+    for s in stats {
+        ui.table_next_column();
+        ui.text(e(&s.boss));
+        ui.table_next_column();
+        ui.text(s.attempts.to_string());
+        ui.table_next_column();
+        ui.text(s.kills.to_string());
+        ui.table_next_column();
+        ui.text(s.wipes().to_string());
+        ui.table_next_column();
+        ui.text(format!("{:.0}%", s.kill_ratio() * 100.0));
+        ui.table_next_column();
+        ui.text(s.first_kill.map(format_timestamp).unwrap_or_default());
+        ui.table_next_column();
+        ui.text(s.fastest_kill.map(format_duration).unwrap_or_default());
+        ui.table_next_column();
+        ui.text(s.cm.to_string());
+        ui.table_next_column();
+        ui.text(s.lcm.to_string());
+    }
+}
@@ -0,0 +1,88 @@
+use std::cell::Cell;
+
+use chrono::Local;
+use log_uploader_core::{
+    settings::Settings,
+    table::{Column, Sort, TimeFormat},
+};
+use nexus::imgui::{TableColumnFlags, TableColumnSetup, TableFlags, TableSortDirection, Ui};
+
+use crate::util::{e, e_fill};
+
+fn setup_column(column: Column, ui: &Ui, settings: &Settings) -> TableColumnSetup<String> {
+    let fixed = |name: String, width: f32| TableColumnSetup {
+        name,
+        flags: TableColumnFlags::WIDTH_FIXED,
+        init_width_or_weight: width + 10.0,
+        user_id: Default::default(),
+    };
+    let time_width = || {
+        let sample = match settings.time_format {
+            // "10 days ago" is about as long as it gets
+            TimeFormat::Relative => e_fill("{} days ago", &[&10]),
+            f => f.format(Local::now(), settings.time_include_date),
+        };
+        ui.calc_text_size(sample)[0]
+    };
+    match column {
+        Column::Encounter => TableColumnSetup {
+            name: e("Encounter"),
+            flags: TableColumnFlags::WIDTH_STRETCH,
+            init_width_or_weight: ui.calc_text_size("Kanaxai, Scythe of House Aurkus (25577)")[0]
+                + 10.0,
+            user_id: Default::default(),
+        },
+        Column::Start => fixed(e("Start"), time_width()),
+        Column::Duration => fixed(e("Duration"), ui.calc_text_size("00m 00s")[0]),
+        Column::Created => fixed(e("Created"), time_width()),
+        // Icon columns don't get a header
+        Column::DpsReport | Column::Wingman => {
+            let marker = if settings.theme.glyphs {
+                ui.calc_text_size(settings.theme.glyph(false))[0] + 2.0
+            } else {
+                0.0
+            };
+            fixed(String::new(), 10.0 + marker)
+        }
+        Column::OpenInFolder => fixed(String::new(), 10.0),
+    }
+}
+
+pub fn setup_table<F: FnOnce(Option<Sort>)>(
+    ui: &Ui,
+    columns: &[Column],
+    settings: &Settings,
+    f: F,
+) {
+    thread_local! {
+        static SORT: Cell<Option<Sort>> = const { Cell::new(None) };
+    }
+    // Tristate so clicking a header a third time goes back to the creation order
+    let flags = TableFlags::BORDERS_OUTER
+        | TableFlags::BORDERS_INNER_V
+        | TableFlags::NO_PAD_INNER_X
+        | TableFlags::SORTABLE
+        | TableFlags::SORT_TRISTATE;
+
+    // Column ids have to change with the column set, otherwise imgui keeps the old widths around
+    let id = format!("{}##{}", e("Uploads"), columns.len());
+    let Some(t) = ui.begin_table_with_flags(id, columns.len(), flags) else {
+        return;
+    };
+    for c in columns {
+        ui.table_setup_column_with(setup_column(*c, ui, settings));
+    }
+    ui.table_headers_row();
+    if let Some(specs) = ui.table_sort_specs_mut() {
+        specs.conditional_sort(|specs| {
+            SORT.set(specs.iter().next().and_then(|spec| {
+                Some(Sort {
+                    column: *columns.get(spec.column_idx())?,
+                    ascending: matches!(spec.sort_direction()?, TableSortDirection::Ascending),
+                })
+            }));
+        });
+    }
+    f(SORT.get());
+    t.end();
+}
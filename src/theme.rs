@@ -0,0 +1,35 @@
+use log_uploader_core::theme::{Palette, Theme};
+use nexus::imgui::Ui;
+
+use crate::util::e;
+
+pub trait ThemeExt {
+    /// Marks the previous item (usually an icon) as failed if glyphs are enabled
+    fn failure_marker(&self, ui: &Ui);
+    fn render(&mut self, ui: &Ui);
+}
+
+impl ThemeExt for Theme {
+    fn failure_marker(&self, ui: &Ui) {
+        if self.glyphs {
+            ui.same_line_with_spacing(0.0, 2.0);
+            ui.text_colored(self.failure(), self.glyph(false));
+        }
+    }
+
+    fn render(&mut self, ui: &Ui) {
+        let mut palette = Palette::ALL
+            .iter()
+            .position(|p| *p == self.palette)
+            .unwrap_or_default();
+        let names = Palette::ALL.map(Palette::name);
+        if ui.combo_simple_string(e("Status colours"), &mut palette, &names) {
+            self.palette = Palette::ALL[palette];
+        }
+        ui.same_line();
+        ui.text_colored(self.success(), self.label(true, e("Kill")));
+        ui.same_line();
+        ui.text_colored(self.failure(), self.label(false, e("Wipe")));
+        ui.checkbox(e("Show status glyphs"), &mut self.glyphs);
+    }
+}
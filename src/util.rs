@@ -1,15 +1,10 @@
-use nexus::{
-    imgui::{StyleColor, Ui},
-    localization::translate,
-};
+use nexus::imgui::{StyleColor, Ui};
 use std::path::Path;
 
 use windows::Win32::UI::Shell::{ILCreateFromPathW, ILFree, SHOpenFolderAndSelectItems};
 use windows::core::{HSTRING, Result};
 
-pub fn e(s: &str) -> String {
-    translate(s).unwrap_or_else(|| s.to_string())
-}
+pub use log_uploader_core::util::{e, e_fill, e_or};
 
 pub fn open_with_selected(path: impl AsRef<Path>) -> Result<()> {
     let path: &Path = path.as_ref();